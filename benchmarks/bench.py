@@ -0,0 +1,57 @@
+#!/usr/bin/env python3
+"""Time csvparser.py runs on generated files.
+
+Compare two revisions by running this with --script pointing at each, e.g.
+  git show <rev>:csvparser.py > /tmp/old.py
+  python3 benchmarks/bench.py --script /tmp/old.py
+"""
+import argparse
+import os
+import subprocess
+import sys
+import tempfile
+import time
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+
+
+def wideFile(path, rows, columns):
+    with open(path, 'w') as f:
+        f.write(','.join('c%d' % index for index in range(columns)) + '\n')
+        line = ','.join('value%d' % index for index in range(columns)) + '\n'
+        for number in range(rows):
+            f.write(line)
+
+
+def timeRun(script, args, repeat):
+    best = None
+    for attempt in range(repeat):
+        start = time.perf_counter()
+        with open(os.devnull, 'w') as devnull:
+            subprocess.run([sys.executable, script] + args, stdout=devnull, check=True)
+        elapsed = time.perf_counter() - start
+        best = elapsed if best is None else min(best, elapsed)
+    return best
+
+
+def main():
+    parser = argparse.ArgumentParser(description='Time csvparser.py on generated inputs.')
+    parser.add_argument('--script', default=os.path.join(ROOT, 'csvparser.py'), help='csvparser.py to time (default: the one in this tree)')
+    parser.add_argument('--rows', type=int, default=100000, help='rows in the generated file (default: 100000)')
+    parser.add_argument('--columns', type=int, default=100, help='columns in the generated file (default: 100)')
+    parser.add_argument('--repeat', type=int, default=3, help='runs per case; the best time is reported (default: 3)')
+    args = parser.parse_args()
+    with tempfile.TemporaryDirectory() as scratch:
+        path = os.path.join(scratch, 'wide.csv')
+        wideFile(path, args.rows, args.columns)
+        cases = [
+            ('all columns', [path]),
+            ('select 3 columns', ['-f', '1,%d,%d' % (args.columns // 2, args.columns), path]),
+            ('count', ['--count', path]),
+        ]
+        for name, caseArgs in cases:
+            print('%-20s %.2fs' % (name, timeRun(args.script, caseArgs, args.repeat)))
+
+
+if __name__ == '__main__':
+    main()
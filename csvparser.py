@@ -1,40 +1,2341 @@
 #!/usr/bin/env python
 # -*- coding: utf-8 -*-
 
+import os
 import sys
+import errno
+import gzip
+import hashlib
+import http.client
+import io
 import argparse
+import base64
+import binascii
+import collections
+import codecs
 import csv
+import heapq
+import datetime
+import random
+import re
+import shutil
+import signal
+import sqlite3
+import struct
+import tempfile
+import itertools
+import json
+import locale
+import math
+import threading
+import time
+import unicodedata
+import urllib.error
+import urllib.parse
+import urllib.request
+import zipfile
+import zlib
 
 version = '%(prog)s 20160808'
 
+EXIT_CONFIG = 2
+EXIT_IO = 3
+EXIT_PARSE = 4
+
+interrupted = threading.Event()
+
+def interrupt(signum, frame):
+    interrupted.set()
+    signal.signal(signal.SIGINT, signal.default_int_handler)
+
+def die(message, status=1):
+    sys.stderr.write('%s\n' % message)
+    sys.exit(status)
+
+def renameRegex(value):
+    parts = value[1:].split(value[:1])
+    if len(value) < 2 or len(parts) != 3 or parts[2] != '':
+        raise argparse.ArgumentTypeError("expected '/pattern/replacement/': %r" % value)
+    try:
+        return re.compile(parts[0]), parts[1]
+    except re.error as e:
+        raise argparse.ArgumentTypeError('invalid pattern %r: %s' % (parts[0], e))
+
+def replacement(value):
+    old, sep, new = value.partition('=')
+    if not sep or not old:
+        raise argparse.ArgumentTypeError("expected 'OLD=NEW': %r" % value)
+    return old, new
+
+def filterSpec(value):
+    match = re.match(r'(.+?)(==|!=|>=|<=|~=|>|<)(.*)$', value)
+    if match is None:
+        raise argparse.ArgumentTypeError("expected 'COLUMN OP VALUE' with OP one of == != > < >= <= ~=: %r" % value)
+    return match.group(1).strip(), match.group(2), match.group(3).strip()
+
+def namedValue(value):
+    name, sep, constant = value.partition('=')
+    if not sep or not name:
+        raise argparse.ArgumentTypeError("expected 'NAME=VALUE': %r" % value)
+    return name, constant
+
+def aggregations(value):
+    specs = []
+    for item in value.split(','):
+        function, sep, column = item.partition(':')
+        if not sep or function not in ('sum', 'count', 'min', 'max', 'mean') or not column or (column == '*' and function != 'count'):
+            raise argparse.ArgumentTypeError("expected 'FUNC:COLUMN,...' with FUNC one of sum count min max mean (count:* counts rows): %r" % item)
+        specs.append((function, column))
+    return specs
+
+def widths(value):
+    parts = value.split(',')
+    if not all(part.isdigit() and int(part) > 0 for part in parts):
+        raise argparse.ArgumentTypeError("expected positive column widths like '10,20,8': %r" % value)
+    return [int(part) for part in parts]
+
+def groupSample(value):
+    column, sep, size = value.rpartition(':')
+    if not sep or not column or not size.isdigit() or int(size) < 1:
+        raise argparse.ArgumentTypeError("expected 'COLUMN:K': %r" % value)
+    return column, int(size)
+
+def joiner(value):
+    return '' if value == 'NONE' else value
+
+def parseDate(value):
+    try:
+        date = datetime.datetime.fromisoformat(value.strip())
+    except ValueError:
+        return None
+    if date.tzinfo is not None:
+        date = date.astimezone(datetime.timezone.utc).replace(tzinfo=None)
+    return date
+
+def retentionCutoff(value):
+    column, sep, cutoff = value.partition(':')
+    date = parseDate(cutoff)
+    if not sep or not column or date is None:
+        raise argparse.ArgumentTypeError("expected 'COLUMN:DATE': %r" % value)
+    return column, date
+
+def runningTotal(value):
+    column, sep, name = value.rpartition(':')
+    if not sep or not column or not name:
+        raise argparse.ArgumentTypeError("expected 'COLUMN:NAME': %r" % value)
+    return column, name
+
+def indexSpec(value):
+    column, sep, path = value.partition(':')
+    if not sep or not column or not path:
+        raise argparse.ArgumentTypeError("expected 'COLUMN:FILE': %r" % value)
+    return column, path
+
+def positiveInt(value):
+    if not value.isdigit() or int(value) < 1:
+        raise argparse.ArgumentTypeError('expected a positive integer: %r' % value)
+    return int(value)
+
+def positiveFloat(value):
+    try:
+        number = float(value)
+    except ValueError:
+        number = 0.0
+    if not number > 0 or math.isinf(number):
+        raise argparse.ArgumentTypeError('expected a positive number: %r' % value)
+    return number
+
+def count(value):
+    if not value.isdigit():
+        raise argparse.ArgumentTypeError('expected a non-negative integer: %r' % value)
+    return int(value)
+
+def megabytes(value):
+    if not value.isdigit() or int(value) < 1:
+        raise argparse.ArgumentTypeError('expected a positive number of megabytes: %r' % value)
+    return int(value) * 1024 * 1024
+
+def tokenizeExpression(text):
+    tokens = []
+    for number, name, quoted, symbol in re.findall(r'\s*(?:(\d+\.?\d*(?:[eE][-+]?\d+)?|\.\d+)|([A-Za-z_]\w*)|"([^"]*)"|(\S))', text):
+        if number:
+            tokens.append(('num', float(number)))
+        elif name or quoted:
+            tokens.append(('col', name or quoted))
+        elif symbol in '+-*/()':
+            tokens.append((symbol, None))
+        else:
+            raise argparse.ArgumentTypeError('unexpected %r in expression %r' % (symbol, text.strip()))
+    return tokens
+
+def parseExpression(tokens, text):
+    def peek():
+        return tokens[0][0] if tokens else None
+
+    def factor():
+        if not tokens:
+            raise argparse.ArgumentTypeError('incomplete expression %r' % text)
+        kind, value = tokens.pop(0)
+        if kind in ('+', '-'):
+            operand = factor()
+            return ('neg', operand) if kind == '-' else operand
+        if kind == '(':
+            node = expression()
+            if peek() != ')':
+                raise argparse.ArgumentTypeError('missing ) in expression %r' % text)
+            tokens.pop(0)
+            return node
+        if kind in ('num', 'col'):
+            return (kind, value)
+        raise argparse.ArgumentTypeError('unexpected %r in expression %r' % (kind, text))
+
+    def term():
+        node = factor()
+        while peek() in ('*', '/'):
+            node = (tokens.pop(0)[0], node, factor())
+        return node
+
+    def expression():
+        node = term()
+        while peek() in ('+', '-'):
+            node = (tokens.pop(0)[0], node, term())
+        return node
+
+    node = expression()
+    if tokens:
+        raise argparse.ArgumentTypeError('unexpected %r in expression %r' % (tokens[0][0], text))
+    return node
+
+def coalesceSpec(value):
+    columns, sep, name = value.rpartition(':')
+    if not sep or not columns or not name:
+        raise argparse.ArgumentTypeError("expected 'A,B,...:NAME': %r" % value)
+    return columns.split(','), name
+
+def computeSpec(value):
+    name, sep, text = value.partition('=')
+    if not sep or not name.strip() or not text.strip():
+        raise argparse.ArgumentTypeError("expected 'NAME = EXPRESSION': %r" % value)
+    return name.strip(), parseExpression(tokenizeExpression(text), text.strip())
+
+def encodingName(value):
+    value = {'utf16le': 'utf-16-le', 'utf16be': 'utf-16-be'}.get(value.lower(), value)
+    try:
+        codecs.lookup(value)
+    except LookupError:
+        raise argparse.ArgumentTypeError('unknown encoding: %r' % value)
+    return value
+
+def delimiterChar(value):
+    value = {'\\t': '\t', 'tab': '\t'}.get(value, value)
+    if len(value) != 1:
+        raise argparse.ArgumentTypeError('expected a single character or \\t: %r' % value)
+    return value
+
+alternatives = [
+    ('fields', 'columns', 'exclude'),
+    ('split_rows', 'split_bytes', 'split_by'),
+    ('progress', 'stats_interval'),
+    ('bgzip_output', 'output_compression'),
+]
+
+def configDefaults(parser, path):
+    try:
+        import tomllib
+    except ImportError:
+        parser.error('--config needs Python 3.11 or later')
+    try:
+        with open(path, 'rb') as configfile:
+            config = tomllib.load(configfile)
+    except OSError as e:
+        parser.error("can't open '%s': %s" % (path, e.strerror))
+    except tomllib.TOMLDecodeError as e:
+        parser.error('invalid config file %s: %s' % (path, e))
+    actions = dict((action.dest, action) for action in parser._actions)
+    defaults = {}
+    for key, value in config.items():
+        dest = key.replace('-', '_')
+        action = actions.get(dest)
+        if action is None or dest in ('help', 'version', 'config'):
+            parser.error('unknown key in config file %s: %s' % (path, key))
+        if isinstance(action, argparse._StoreTrueAction):
+            if not isinstance(value, bool):
+                parser.error('config key %s must be true or false' % key)
+        elif isinstance(action, argparse._AppendAction) or action.nargs == '*':
+            values = value if isinstance(value, list) else [value]
+            try:
+                value = [action.type(str(item)) if action.type else str(item) for item in values]
+            except argparse.ArgumentTypeError as e:
+                parser.error('config key %s: %s' % (key, e))
+        else:
+            if isinstance(value, list):
+                value = ','.join(str(item) for item in value)
+            value = str(value)
+            if action.type:
+                try:
+                    value = action.type(value)
+                except (argparse.ArgumentTypeError, TypeError, ValueError) as e:
+                    parser.error('config key %s: %s' % (key, e))
+            if action.choices and value not in action.choices:
+                parser.error('config key %s: invalid choice %r (choose from %s)' % (key, value, ', '.join(str(choice) for choice in action.choices)))
+        defaults[dest] = value
+    return defaults
+
 def ArgParse():
     parser = argparse.ArgumentParser(description=
     '''This script parse csv data.
 
     Create Date: 2016-08-08 ''',
+    epilog='''exit status:
+  0  success
+  1  other failures, e.g. --dry-validate problems or --fail-missing keys
+  2  usage or configuration error, e.g. an unknown option or column, or
+     --max-memory exceeded
+  3  I/O error reading input or writing output
+  4  malformed or invalid input, e.g. a bad row or a failed check
+  130  interrupted; the rows read so far were written out completely''',
     formatter_class=argparse.RawDescriptionHelpFormatter)
 
-    parser.add_argument('infile', nargs='?', type=argparse.FileType('rU'), metavar='FILE', help='CSV File', default=sys.stdin)
-    parser.add_argument('-f', '--fields', action='store', type=str, metavar='LIST', help='select only these fields')
+    parser.add_argument('infiles', nargs='*', type=str, metavar='FILE', help='CSV File (stdin if none or -), or an http:// or https:// URL streamed as it downloads; several files with the same header are read as one stream')
+    parser.add_argument('--timeout', action='store', type=positiveFloat, default=30.0, metavar='SECONDS', help='give up on a URL input that sends nothing for SECONDS (default: 30)')
+    parser.add_argument('-o', '--output', action='store', type=str, metavar='FILE', help='write to FILE instead of stdout (replaced only on success, or with the rows written so far after Ctrl-C)')
+    parser.add_argument('--bgzip-output', action='store_true', help='write --output as bgzip-compatible blocked gzip with a .gzi index next to it')
+    parser.add_argument('--output-delimiter', action='store', type=delimiterChar, metavar='CHAR', help='field delimiter for output (default: the --delimiter)')
+    parser.add_argument('--tsv-out', dest='output_delimiter', action='store_const', const='\t', help='write tab-separated output; same as --output-delimiter \\t')
+    parser.add_argument('--output-compression', choices=['none', 'gzip', 'zstd'], help='compress the output stream (zstd needs the zstandard module)')
+    parser.add_argument('-d', '--delimiter', action='store', type=delimiterChar, default=',', metavar='CHAR', help='field delimiter for input and output, e.g. ";" or \\t (default: ,)')
+    parser.add_argument('--quote', action='store', type=delimiterChar, default='"', metavar='CHAR', help='quote character for input and output (default: ")')
+    parser.add_argument('--escape', action='store', type=delimiterChar, metavar='CHAR', help='escape character, e.g. \\, used instead of doubling the quote character, for input and output')
+    parser.add_argument('--no-quoting', action='store_true', help='treat quote characters in the input as ordinary data')
+    parser.add_argument('--fixed-width', action='store', type=widths, metavar='WIDTHS', help='read fixed-width input, cutting each line into fields of these widths in characters, e.g. 10,20,8; text past the last field is ignored')
+    parser.add_argument('--fixed-width-keep-spaces', action='store_true', help='keep the trailing spaces of --fixed-width fields')
+    parser.add_argument('--fixed-width-pad', action='store_true', help='pad --fixed-width lines that are too short instead of treating them as malformed (see --on-error)')
+    parser.add_argument('--detect-delimiter', action='store_true', help='pick the delimiter among , tab ; and | that splits the first 20 lines into the same number of fields')
+    parser.add_argument('-f', '--fields', '--field', dest='fields', action='store', type=str, metavar='LIST', help='select only these fields, e.g. 1,3-5,8- (ranges are inclusive; N- runs to the last column)')
+    parser.add_argument('-c', '--columns', action='store', type=str, metavar='NAMES', help='select only the fields with these header names')
+    parser.add_argument('-x', '--exclude', action='store', type=str, metavar='COLUMNS', help='drop these fields (names or indices) and keep the rest in order')
+    parser.add_argument('--index-base', '--columns-index-base', dest='index_base', action='store', type=int, choices=[0, 1], default=1, help='number fields from 0 or 1 (default: 1)')
+    parser.add_argument('--pick', action='store_true', help='choose fields interactively from the header (TTY only)')
+    parser.add_argument('--encoding', action='store', type=encodingName, metavar='NAME', help='input text encoding, e.g. utf-8, utf-16-le or latin-1 (default: the locale encoding); a leading UTF-8 or UTF-16 byte order mark is detected and removed')
+    parser.add_argument('--input-compression', choices=['none', 'gzip'], help='decompress input (default: gzip if FILE ends with .gz)')
+    parser.add_argument('--zip', action='store_true', help='read the input as a zip archive (implied by a .zip FILE); all .csv members are concatenated and must share the same header')
+    parser.add_argument('--zip-member', action='store', type=str, metavar='NAME', help='read only the zip member NAME')
+    parser.add_argument('--wait-for-input', action='store', type=positiveFloat, metavar='SECONDS', help='wait up to SECONDS for FILE to appear before giving up')
+    parser.add_argument('--base64-input', action='store_true', help='decode base64-encoded input before parsing')
+    parser.add_argument('--max-memory', action='store', type=megabytes, metavar='MB', help='soft memory cap: bounds read buffers and aborts operations that hold rows in memory once peak RSS exceeds MB')
+    parser.add_argument('--auto-buffer', action='store_true', help='size the read buffer from the input file size (64KB to 4MB)')
+    parser.add_argument('--build-index', action='store', type=indexSpec, metavar='COLUMN:INDEX', help='write INDEX, a CSV with a key,offset header and one row per record in input order: the COLUMN value and the byte offset where the record starts')
+    parser.add_argument('--lookup', action='store', type=str, metavar='INDEX', help='print the records whose key is given by --key, using an index from --build-index; the index is read once, keeping only those keys, and each record is read with a single seek')
+    parser.add_argument('--key', action='append', type=str, metavar='KEY', help='key to look up (repeatable)')
+    parser.add_argument('--fail-missing', action='store_true', help='exit with status 1 if a looked-up key is not in the index')
+    parser.add_argument('--prescan', action='store_true', help='count records before processing for an accurate progress total (reads the input twice)')
+    parser.add_argument('--stats', action='store_true', help='print final processing stats (records, bytes read, rates, peak memory) to stderr')
+    parser.add_argument('--stats-format', choices=['text', 'json'], help='format of the final stats; json gives records, bytes, rates, rejected and duplicate counts, peak memory and the --profile table (implies --stats)')
+    parser.add_argument('--stats-file', action='store', type=str, metavar='FILE', help='write the final stats to FILE instead of stderr (implies --stats)')
+    parser.add_argument('--stats-interval', action='store', type=positiveFloat, metavar='SECONDS', help='print processing stats to stderr every SECONDS')
+    parser.add_argument('--rate-window', action='store', type=positiveFloat, default=5.0, metavar='SECONDS', help='window for the recent records/sec shown next to the overall rate in stats and progress output (default: 5)')
+    parser.add_argument('--progress', action='store_true', help='show a progress bar with throughput and ETA on stderr when it is a terminal (a spinner with records/sec when the input size is unknown)')
+    parser.add_argument('--timing-histogram', action='store_true', help='print per-record processing time percentiles to stderr at the end')
+    parser.add_argument('--count', action='store_true', help='print only the number of data rows instead of writing them')
+    parser.add_argument('--on-error', choices=['fail', 'skip', 'collect'], default='fail', help='on a malformed row: stop, warn and skip it, or skip it and report all of them at the end (default: fail)')
+    parser.add_argument('--validate', action='store_true', help='treat rows whose field count differs from the header as errors, handled per --on-error')
+    parser.add_argument('--reject-file', action='store', type=str, metavar='FILE', help='write malformed rows and rows with the wrong number of fields to FILE (line, record, error) and keep going')
+    parser.add_argument('--dry-validate', action='store_true', help='run the configured checks without writing any output; report every problem and exit 1 if there were any')
+    parser.add_argument('--strict', action='store_true', help='with --dry-validate, stop at the first problem')
+    parser.add_argument('--validate-no-newlines', action='store_true', help='fail on the first record with a newline inside a field')
+    parser.add_argument('--start-marker', action='store', type=str, metavar='LINE', help='ignore input up to and including the line LINE')
+    parser.add_argument('--end-marker', action='store', type=str, metavar='LINE', help='stop reading at the line LINE')
+    parser.add_argument('--comment-char', action='store', type=delimiterChar, metavar='CHAR', help='ignore lines starting with CHAR, including before the header')
+    parser.add_argument('--no-headers', action='store_true', help='the input has no header row; columns are addressed by position only and no header is written')
+    parser.add_argument('--header-rows', action='store', type=positiveInt, default=1, metavar='N', help='number of header rows; the last one names the columns unless --flatten-headers is given (default: 1)')
+    parser.add_argument('--flatten-headers', action='store_true', help='combine all header rows into names like group.sub, carrying empty group cells forward')
+    parser.add_argument('--header-joiner', action='store', type=str, default='.', metavar='SEP', help='separator for --flatten-headers (default: .)')
+    parser.add_argument('--trim', choices=['none', 'headers', 'fields', 'all'], default='none', help='strip whitespace around unquoted header names, data fields or both; quoted values keep their spaces (default: none)')
+    parser.add_argument('--trim-header-names', action='store_true', help='strip surrounding whitespace from header names')
+    parser.add_argument('--assert-monotonic', action='store', type=str, metavar='COLUMN', help='fail if the numeric or date values of COLUMN ever decrease')
+    parser.add_argument('--monotonic-strict', action='store_true', help='with --assert-monotonic, also fail on repeated values')
+    parser.add_argument('--rename-regex', action='store', type=renameRegex, metavar='/PATTERN/REPL/', help='rename every header name matching PATTERN')
+    parser.add_argument('--rename', action='append', type=replacement, metavar='OLD=NEW', help='rename column OLD to NEW in the output header (repeatable); OLD is the input name, which --rename-regex leaves alone, and renaming happens after field selection')
+    parser.add_argument('--columns-dedup', action='store_true', help='drop later columns whose header repeats an earlier name; --fields then numbers the remaining columns')
+    parser.add_argument('--columns-only-nonempty', action='store_true', help='drop columns that are empty in every row; rows are spooled to a temporary file for a second pass, and --fields numbers the remaining columns')
+    parser.add_argument('--select-type', action='store', choices=['numeric', 'integer', 'number', 'boolean', 'date', 'string'], help='keep only columns of this inferred type (date includes timestamps); --fields numbers the remaining columns')
+    parser.add_argument('--json-schema-out', action='store', type=str, metavar='FILE', help='write a JSON Schema (draft-07) for the output columns, inferred from the first --infer-rows rows')
+    parser.add_argument('--infer-rows', action='store', type=positiveInt, default=1000, metavar='N', help='infer column types from the first N data rows only; later rows are not checked (default: 1000)')
+    parser.add_argument('--schema-order-file', action='store', type=str, metavar='FILE', help='reorder columns to the names listed one per line in FILE, adding empty columns for missing names')
+    parser.add_argument('--drop-extra', action='store_true', help='with --schema-order-file, drop columns not listed instead of appending them')
+    parser.add_argument('--min-columns', action='store', type=positiveInt, metavar='N', help='pad the header and every output row with empty fields to at least N columns')
+    parser.add_argument('--line-buffered', action='store_true', help='flush the output after every complete record')
+    parser.add_argument('--output-format', '--format', action='store', choices=['csv', 'null-delimited', 'jsonl', 'avro', 'table'], default='csv', help='output format; null-delimited writes one field per record terminated by NUL, without the header; jsonl writes one JSON object per row keyed by header name, which must be unique (an array per row with --no-headers); avro writes an Avro object container file with a schema inferred from the first --infer-rows rows; table prints the first --table-rows rows as an aligned box-drawn table')
+    parser.add_argument('--table-rows', action='store', type=positiveInt, default=100, metavar='N', help='rows shown by --format table; reading stops after them (default: 100)')
+    parser.add_argument('--max-col-width', action='store', type=positiveInt, default=40, metavar='N', help='truncate --format table cells wider than N terminal columns with an ellipsis (default: 40)')
+    parser.add_argument('--field-separator-in-output', action='store', type=joiner, metavar='SEP', help='join the output fields into a single column with SEP (NONE for no separator)')
+    parser.add_argument('--on-field-count-mismatch-fill', action='store', type=str, metavar='SENTINEL', help='fill fields missing from rows shorter than the header with SENTINEL')
+    parser.add_argument('--replace', action='append', type=replacement, metavar='OLD=NEW', help='replace OLD with NEW inside field values (repeatable)')
+    parser.add_argument('--replace-regex', action='append', type=renameRegex, metavar='/PATTERN/REPL/', help='replace matches of PATTERN inside field values (repeatable, applied after --replace)')
+    parser.add_argument('--replace-column', action='store', type=str, metavar='COLUMNS', help='restrict --replace and --replace-regex to these columns')
+    parser.add_argument('--filter', action='store', type=filterSpec, metavar='EXPR', help="keep only rows where EXPR holds; EXPR is COLUMN OP VALUE with OP one of == != > < >= <= (numeric when both sides are numbers, otherwise string) or ~= (substring), e.g. 'status==active' or 'amount>100'")
+    parser.add_argument('--dedupe', action='store_true', help='drop rows identical to an earlier row; only a 64-bit hash of each row is kept, so memory grows with the number of distinct rows and a hash collision can very rarely drop a unique row; past --max-memory the hashes move to a temporary on-disk table')
+    parser.add_argument('--dedupe-by', action='store', type=str, metavar='COLUMNS', help='like --dedupe, but rows are duplicates when these columns match')
+    parser.add_argument('--sort-by', action='store', type=str, metavar='COLUMN', help='sort rows by COLUMN with an external merge sort; runs of --sort-buffer rows are sorted in memory and spilled to temporary files')
+    parser.add_argument('--sort-desc', action='store_true', help='sort in descending order')
+    parser.add_argument('--numeric', action='store_true', help='compare --sort-by values as numbers; non-numeric values sort after all numbers (before them with --sort-desc)')
+    parser.add_argument('--sort-buffer', action='store', type=positiveInt, default=200000, metavar='N', help='rows per in-memory sort run; lower it if a run exceeds --max-memory (default: 200000)')
+    parser.add_argument('--temp-dir', action='store', type=str, metavar='DIR', help='directory for sort runs (default: the system temporary directory)')
+    parser.add_argument('--group-by', action='store', type=str, metavar='COLUMN', help='write one row per run of equal COLUMN values with the --agg results; the input must already be grouped (see --sort-by)')
+    parser.add_argument('--agg', action='store', type=aggregations, metavar='FUNC:COLUMN,...', help="aggregates for --group-by, e.g. 'sum:amount,count:*'; FUNC is sum, count, min, max or mean")
+    parser.add_argument('--group-strict', action='store_true', help='fail instead of warning when a --group-by value appears again after another group')
+    parser.add_argument('--add-column', action='append', type=namedValue, metavar='NAME=VALUE', help='append a column NAME holding VALUE after the selected fields (repeatable); {filename}, {rownum} and {timestamp} in VALUE are expanded per row')
+    parser.add_argument('--skip', action='store', type=count, default=0, metavar='N', help='skip the first N data rows left after --filter, --dedupe, --sort-by and --group-by (the header is kept)')
+    parser.add_argument('--limit', action='store', type=count, metavar='N', help='stop after N data rows left after --filter, --dedupe, --sort-by and --group-by; without those the rest of the input is not read')
+    parser.add_argument('--sample-head-tail', action='store', type=positiveInt, metavar='N', help='keep only the first N and last N data rows')
+    parser.add_argument('--sample-per-group', action='store', type=groupSample, metavar='COLUMN:K', help='keep at most K random rows per distinct COLUMN value (memory grows with groups x K)')
+    parser.add_argument('--seed', action='store', type=int, metavar='S', help='random seed for sampling')
+    parser.add_argument('--running-total', action='store', type=runningTotal, metavar='COLUMN:NAME', help='append a column NAME with the cumulative sum of COLUMN (non-numeric values count as 0)')
+    parser.add_argument('--running-total-group', action='store', type=str, metavar='COLUMN', help='restart the running total whenever COLUMN changes value')
+    parser.add_argument('--profile', action='store_true', help='print per-column empty counts, min/max value length and an approximate distinct count to stderr at the end')
+    parser.add_argument('--aggregate', action='store', type=str, metavar='COLUMNS', help='print count, sum, mean, min and max of these columns to stderr at the end; non-numeric values are counted as skipped')
+    parser.add_argument('--column-stats-out', action='store', type=str, metavar='FILE', help='write count, min, max, mean and sample stddev of each numeric column to FILE')
+    parser.add_argument('--coalesce', action='append', type=coalesceSpec, metavar='A,B,...:NAME', help='append a column NAME with the first non-empty value of the listed columns (repeatable)')
+    parser.add_argument('--coalesce-drop', action='store_true', help='remove the source columns of --coalesce')
+    parser.add_argument('--compute', action='append', type=computeSpec, metavar="'NAME = EXPR'", help='append a column NAME computed with + - * / and parentheses over numeric columns; empty when an input is not a number (repeatable)')
+    parser.add_argument('--retention', action='store', type=retentionCutoff, metavar='COLUMN:DATE', help='route records dated before DATE to --old-out and the rest to --current-out')
+    parser.add_argument('--old-out', action='store', type=str, metavar='FILE', help='output for records older than the retention cutoff')
+    parser.add_argument('--current-out', action='store', type=str, metavar='FILE', help='output for records at or after the retention cutoff')
+    parser.add_argument('--retention-unparseable', action='store', choices=['old', 'current', 'drop'], default='current', help='where records with an unparseable date go (default: current)')
+    parser.add_argument('--split-rows', action='store', type=positiveInt, metavar='N', help='write --output as numbered parts (out.part00001.csv, ...) of at most N rows, each with the header')
+    parser.add_argument('--split-bytes', action='store', type=positiveInt, metavar='N', help='like --split-rows, but start a new part before a part would exceed N bytes including its header')
+    parser.add_argument('--split-by', action='store', type=str, metavar='COLUMN', help='write each row to --output with the COLUMN value added to the name (out_<value>.csv), one file per value, each with the header; values that clean up to the same name get a numbered suffix')
+    parser.add_argument('--split-max-open', action='store', type=positiveInt, default=64, metavar='N', help='keep at most N --split-by files open, closing the least recently used (default: 64)')
+    parser.add_argument('--config', action='store', type=str, metavar='FILE', help='read option defaults from a TOML file of option = value pairs (Python 3.11+); command-line options take precedence, including over alternatives such as --fields for --columns')
     parser.add_argument('-v', '--version', action='version', version=version)
+    preparser = argparse.ArgumentParser(add_help=False)
+    preparser.add_argument('--config')
+    known, rest = preparser.parse_known_args()
+    if known.config:
+        defaults = configDefaults(parser, known.config)
+        given = parser.parse_args()
+        for group in alternatives:
+            # An option on the command line replaces its alternatives from the file.
+            if any(getattr(given, dest) != parser.get_default(dest) for dest in group):
+                for dest in group:
+                    defaults.pop(dest, None)
+        parser.set_defaults(**defaults)
     args = parser.parse_args()
+    args.infile = args.infiles[0] if args.infiles and args.infiles[0] != '-' else None
+    if len(args.infiles) > 1 and (args.zip or args.zip_member is not None or args.build_index or args.lookup or args.pick or args.prescan):
+        parser.error('several FILEs cannot be used with --zip, --zip-member, --build-index, --lookup, --pick or --prescan')
+    if args.build_index and (args.zip or args.zip_member is not None or (args.infile or '').lower().endswith('.zip')):
+        parser.error('--build-index cannot be used with zip input')
+    if args.build_index and (args.input_compression == 'gzip' or (args.input_compression is None and (args.infile or '').lower().endswith('.gz'))):
+        parser.error('--build-index cannot be used with gzip input')
+    if sum(option is not None for option in (args.fields, args.columns, args.exclude)) > 1:
+        parser.error('--fields, --columns and --exclude cannot be used together')
+    if args.no_headers:
+        if args.columns is not None:
+            parser.error('--columns selects by header name and cannot be used with --no-headers')
+        if args.header_rows != 1 or args.build_index or args.lookup or args.pick:
+            parser.error('--no-headers cannot be used with --header-rows, --build-index, --lookup or --pick')
+    if args.validate and args.on_field_count_mismatch_fill is not None:
+        parser.error('--validate cannot be used with --on-field-count-mismatch-fill')
+    if args.count and (args.dry_validate or args.retention or args.lookup):
+        parser.error('--count cannot be used with --dry-validate, --retention or --lookup')
+    if args.split_rows or args.split_bytes or args.split_by:
+        if bool(args.split_rows) + bool(args.split_bytes) + bool(args.split_by) > 1:
+            parser.error('--split-rows, --split-bytes and --split-by cannot be used together')
+        if not args.output:
+            parser.error('--split-rows, --split-bytes and --split-by require --output')
+        if args.output_format != 'csv' or args.bgzip_output or args.output_compression not in (None, 'none') or args.count or args.dry_validate or args.retention:
+            parser.error('--split-rows, --split-bytes and --split-by only write plain csv output')
+    if bool(args.group_by) != bool(args.agg):
+        parser.error('--group-by and --agg must be given together')
+    if args.group_strict and not args.group_by:
+        parser.error('--group-strict requires --group-by')
+    if (args.sort_desc or args.numeric) and not args.sort_by:
+        parser.error('--sort-desc and --numeric require --sort-by')
+    if args.max_memory and peakMemory() is None:
+        parser.error('--max-memory cannot measure memory use on this platform')
+    if (args.table_rows != 100 or args.max_col_width != 40) and args.output_format != 'table':
+        parser.error('--table-rows and --max-col-width require --format table')
+    if (args.fixed_width_keep_spaces or args.fixed_width_pad) and not args.fixed_width:
+        parser.error('--fixed-width-keep-spaces and --fixed-width-pad require --fixed-width')
+    if any(isUrl(path) for path in args.infiles):
+        if args.zip or args.zip_member is not None or args.build_index or args.pick or args.prescan or args.wait_for_input or args.auto_buffer:
+            parser.error('a URL input cannot be used with zip input, --build-index, --pick, --prescan, --wait-for-input or --auto-buffer')
+    if args.fixed_width and (args.detect_delimiter or args.build_index or args.lookup or args.zip or args.zip_member is not None or len(args.infiles) > 1):
+        parser.error('--fixed-width cannot be used with --detect-delimiter, --build-index, --lookup, zip input or several FILEs')
+    if args.detect_delimiter and (args.build_index or args.lookup):
+        parser.error('--detect-delimiter cannot be used with --build-index or --lookup')
+    if args.progress and args.stats_interval:
+        parser.error('--progress and --stats-interval cannot be used together')
+    if args.bgzip_output and not args.output:
+        parser.error('--bgzip-output requires --output')
+    if args.bgzip_output and args.output_compression not in (None, 'none'):
+        parser.error('--bgzip-output and --output-compression cannot be used together')
+    if bool(args.lookup) != bool(args.key):
+        parser.error('--lookup and --key must be given together')
+    if args.lookup and args.infile is None:
+        parser.error('--lookup requires a FILE argument')
+    if args.replace_column and not (args.replace or args.replace_regex):
+        parser.error('--replace-column requires --replace or --replace-regex')
+    if args.add_column and args.lookup:
+        parser.error('--add-column cannot be used with --lookup')
+    if args.coalesce_drop and not args.coalesce:
+        parser.error('--coalesce-drop requires --coalesce')
+    if args.strict and not args.dry_validate:
+        parser.error('--strict requires --dry-validate')
+    if args.monotonic_strict and not args.assert_monotonic:
+        parser.error('--monotonic-strict requires --assert-monotonic')
+    if args.running_total_group and not args.running_total:
+        parser.error('--running-total-group requires --running-total')
+    if args.retention and not (args.old_out and args.current_out):
+        parser.error('--retention requires --old-out and --current-out')
+    if args.retention and args.output:
+        parser.error('--retention writes to --old-out and --current-out; --output is not used')
+    for field in args.fields.split(',') if args.fields is not None else []:
+        match = re.match(r'(\d+)(-(\d*))?$', field)
+        if match is None:
+            parser.error('invalid field: %s' % field)
+        if int(match.group(1)) < args.index_base:
+            parser.error('field indices are 1-based; 0 is invalid')
+        if match.group(3) and int(match.group(3)) < int(match.group(1)):
+            parser.error('invalid field range: %s' % field)
+    outputs = [args.output, args.old_out, args.current_out, args.reject_file, args.column_stats_out, args.json_schema_out, args.stats_file, args.build_index and args.build_index[1]]
+    for infile in args.infiles:
+        if isUrl(infile):
+            continue
+        for path in outputs:
+            if path and os.path.exists(path) and os.path.exists(infile) and os.path.samefile(path, infile):
+                parser.error('%s is both the input and an output' % path)
+    # The stats are written last; fail before reading any input.
+    if args.stats_file:
+        checkWritable(args.stats_file)
 
     return args
 
-def dumpCsv(infile, fields):
-    reader = csv.reader(infile)
-    writer = csv.writer(sys.stdout)
-    if fields == 0 or fields == ['0']:
-        for row in reader:
-            writer.writerow(row)
+class Histogram(object):
+    def __init__(self):
+        self.counts = {}
+        self.samples = 0
+
+    def record(self, seconds):
+        bucket = float('%.2g' % seconds)
+        self.counts[bucket] = self.counts.get(bucket, 0) + 1
+        self.samples += 1
+
+    def percentile(self, percent):
+        running = 0
+        for bucket in sorted(self.counts):
+            running += self.counts[bucket]
+            if running >= self.samples * percent / 100.0:
+                return bucket
+        return 0.0
+
+    def summary(self):
+        return '%d samples, p50 %.3gus, p90 %.3gus, p99 %.3gus, max %.3gus' % (
+            self.samples, self.percentile(50) * 1e6, self.percentile(90) * 1e6,
+            self.percentile(99) * 1e6, self.percentile(100) * 1e6)
+
+class Stats(object):
+    def __init__(self, total=None, histogram=None, counter=None, window=5.0):
+        self.records = 0
+        self.rejected = 0
+        self.duplicates = 0
+        self.profile = None
+        self.counter = counter
+        self.total = total
+        self.start = time.time()
+        self.histogram = histogram
+        self.last = time.perf_counter()
+        self.window = window
+        self.samples = collections.deque([(self.last, 0)], maxlen=256)
+
+    def count(self):
+        self.records += 1
+        if self.records & 1023 == 0:
+            now = time.perf_counter()
+            if now - self.samples[-1][0] >= self.window / 32:
+                self.samples.append((now, self.records))
+        if self.histogram is not None:
+            now = time.perf_counter()
+            self.histogram.record(now - self.last)
+            self.last = now
+
+    def recentRate(self):
+        now = time.perf_counter()
+        samples = list(self.samples)
+        since, records = samples[0]
+        for when, count in samples:
+            if when > now - self.window:
+                break
+            since, records = when, count
+        return (self.records - records) / (now - since) if now > since else 0.0
+
+    def snapshot(self):
+        elapsed = time.time() - self.start
+        rate = self.records / elapsed if elapsed > 0 else 0.0
+        if self.total:
+            progress = '%d/%d records (%.1f%%)' % (self.records, self.total, 100.0 * self.records / self.total)
+            if rate > 0:
+                progress += ', eta %.1fs' % (max(self.total - self.records, 0) / rate)
+        else:
+            progress = '%d records' % self.records
+        progress = '%s, %.1fs elapsed, %.1f records/sec (%.1f over the last %gs)' % (progress, elapsed, rate, self.recentRate(), self.window)
+        if self.rejected:
+            progress += ', %d rejected' % self.rejected
+        if self.duplicates:
+            progress += ', %d duplicates dropped' % self.duplicates
+        if self.counter is not None:
+            read = self.counter.bytes / (1024.0 * 1024)
+            progress += ', %d bytes (%.1fMB) read, %.1fMB/sec' % (self.counter.bytes, read, read / elapsed if elapsed > 0 else 0.0)
+        current, peak = ['unavailable' if rss is None else '%.1fMB' % (rss / (1024.0 * 1024)) for rss in (currentMemory(), peakMemory())]
+        return '%s, rss %s, peak rss %s' % (progress, current, peak)
+
+    def summary(self):
+        elapsed = time.time() - self.start
+        summary = collections.OrderedDict([
+            ('records', self.records),
+            ('total', self.total),
+            ('bytes', self.counter.bytes if self.counter is not None else None),
+            ('seconds', round(elapsed, 3)),
+            ('records_per_sec', round(self.records / elapsed, 1) if elapsed > 0 else 0.0),
+            ('recent_records_per_sec', round(self.recentRate(), 1)),
+            ('bytes_per_sec', round(self.counter.bytes / elapsed, 1) if elapsed > 0 and self.counter is not None else None),
+            ('rejected', self.rejected),
+            ('duplicates', self.duplicates),
+            ('rss_bytes', currentMemory()),
+            ('peak_rss_bytes', peakMemory()),
+        ])
+        if self.profile is not None:
+            header, profile = self.profile
+            summary['profile'] = profile.summary(header)
+        return summary
+
+def writeStatsFile(path, text):
+    try:
+        with open(path, 'w') as statsfile:
+            statsfile.write(text)
+    except OSError as e:
+        die("can't write '%s': %s" % (path, e.strerror), EXIT_IO)
+
+def checkWritable(path):
+    directory = os.path.dirname(path) or '.'
+    if os.path.isdir(path):
+        problem = errno.EISDIR
+    elif not os.path.isdir(directory):
+        problem = errno.ENOENT
+    elif not os.access(path if os.path.exists(path) else directory, os.W_OK):
+        problem = errno.EACCES
+    else:
+        return
+    die("can't write '%s': %s" % (path, os.strerror(problem)), EXIT_IO)
+
+def reportStats(stats, interval, done):
+    while not done.wait(interval):
+        sys.stderr.write('stats: %s\n' % stats.snapshot())
+
+def inputSize(paths):
+    if not paths or not all(os.path.isfile(path) for path in paths):
+        return None
+    return sum(os.path.getsize(path) for path in paths)
+
+def countRecords(lines, args):
+    record = []
+    def uncommented():
+        for line in lines:
+            if args.comment_char is not None and not record and line.startswith(args.comment_char):
+                continue
+            record.append(line)
+            yield line
+    if args.fixed_width:
+        return sum(1 for line in uncommented())
+    reader = csv.reader(uncommented(), **readerOptions(args))
+    count = 0
+    while True:
+        del record[:]
+        try:
+            next(reader)
+        except StopIteration:
+            return count
+        except csv.Error:
+            pass
+        count += 1
+
+def reportProgress(stats, total, done):
+    spinner = itertools.cycle('|/-\\')
+    while True:
+        finished = done.wait(0.2)
+        elapsed = time.time() - stats.start
+        read = stats.counter.bytes
+        rate = read / elapsed if elapsed > 0 else 0.0
+        if stats.total or total:
+            # A --prescan record count is exact; the input size is not once
+            # the input is compressed or spans comments.
+            position, size = (stats.records, stats.total) if stats.total else (read, total)
+            fraction = min(float(position) / size, 1.0)
+            bar = '#' * int(fraction * 30)
+            line = '[%-30s] %5.1f%% %.1fMB/sec, %.1f records/sec now' % (bar, 100 * fraction, rate / (1024 * 1024), stats.recentRate())
+            if stats.total:
+                line += ', %d/%d records' % (stats.records, stats.total)
+            speed = position / elapsed if elapsed > 0 else 0.0
+            if speed > 0 and not finished:
+                line += ' eta %.0fs' % (max(size - position, 0) / speed)
+        else:
+            line = '%s %d records, %.1f records/sec, %.1f now' % (next(spinner), stats.records, stats.records / elapsed if elapsed > 0 else 0.0, stats.recentRate())
+        sys.stderr.write('\r\x1b[K' + line + ('\n' if finished else ''))
+        sys.stderr.flush()
+        if finished:
+            return
+
+def pickFields(stdscr, header):
+    import curses
+    checked = [True] * len(header)
+    pos = 0
+    curses.curs_set(0)
+    while True:
+        stdscr.erase()
+        height, width = stdscr.getmaxyx()
+        stdscr.addnstr(0, 0, 'space: toggle  a: all  enter: confirm  q: cancel', width - 1)
+        top = max(0, pos - (height - 3))
+        for row, index in enumerate(range(top, min(len(header), top + height - 2))):
+            mark = 'x' if checked[index] else ' '
+            attr = curses.A_REVERSE if index == pos else curses.A_NORMAL
+            stdscr.addnstr(row + 2, 0, '[%s] %d: %s' % (mark, index + 1, header[index]), width - 1, attr)
+        key = stdscr.getch()
+        if key in (curses.KEY_UP, ord('k')):
+            pos = max(0, pos - 1)
+        elif key in (curses.KEY_DOWN, ord('j')):
+            pos = min(len(header) - 1, pos + 1)
+        elif key == ord(' '):
+            checked[pos] = not checked[pos]
+        elif key == ord('a'):
+            checked = [not all(checked)] * len(header)
+        elif key in (curses.KEY_ENTER, ord('\n'), ord('\r')):
+            return checked
+        elif key in (ord('q'), 27):
+            return None
+
+def resolvePick(checked, base):
+    if checked is None or not any(checked):
+        return None
+    return [str(index + base) for index, keep in enumerate(checked) if keep]
+
+def renameHeader(header, args):
+    before = collections.Counter(header)
+    if args.trim_header_names:
+        header = [name.strip() for name in header]
+    if args.rename_regex:
+        pattern, repl = args.rename_regex
+        # Columns given an explicit --rename keep their input name for it to find.
+        explicit = set(old for old, new in args.rename or [])
+        header = [name if name in explicit else pattern.sub(repl, name) for name in header]
+    if not args.columns_dedup:
+        for name, count in collections.Counter(header).items():
+            if count > max(before[name], 1):
+                die('duplicate header after renaming: %s' % name, EXIT_CONFIG)
+    return header
+
+def renameColumns(header, renames):
+    header = list(header)
+    for old, new in renames:
+        if old not in header:
+            die('no such column: %s' % old, EXIT_CONFIG)
+        header[header.index(old)] = new
+    return header
+
+def flattenHeaders(headers, joiner):
+    width = max(len(row) for row in headers)
+    levels = []
+    for row in headers[:-1]:
+        filled = []
+        group = ''
+        for index in range(width):
+            if index < len(row) and row[index]:
+                group = row[index]
+            filled.append(group)
+        levels.append(filled)
+    levels.append(headers[-1] + [''] * (width - len(headers[-1])))
+    return [joiner.join(part for part in parts if part) for parts in zip(*levels)]
+
+def uniqueColumns(header):
+    seen = set()
+    indices = []
+    for index, name in enumerate(header):
+        if name not in seen:
+            seen.add(name)
+            indices.append(index)
+    return indices
+
+def bufferSize(filesize, limit):
+    size = min(max(filesize // 1024, 64 * 1024), 4 * 1024 * 1024)
+    if limit:
+        size = min(size, max(limit // 64, 4096))
+    return size
+
+def peakMemory():
+    try:
+        import resource
+    except ImportError:
+        return currentMemory()
+    usage = resource.getrusage(resource.RUSAGE_SELF).ru_maxrss
+    usage = usage if sys.platform == 'darwin' else usage * 1024
+    return max(usage, currentMemory() or 0)
+
+def currentMemory():
+    try:
+        with open('/proc/self/statm') as statm:
+            return int(statm.read().split()[1]) * os.sysconf('SC_PAGE_SIZE')
+    except (OSError, ValueError, IndexError):
+        return None
+
+def checkMemory(limit, operation):
+    if limit and (peakMemory() or 0) > limit:
+        die('%s exceeded --max-memory %dMB' % (operation, limit // (1024 * 1024)), EXIT_CONFIG)
+
+class ByteCounter(io.RawIOBase):
+    def __init__(self, raw):
+        self.raw = raw
+        self.bytes = 0
+
+    def readable(self):
+        return True
+
+    def readinto(self, buffer):
+        # A single read: a full readinto on a pipe would wait for a whole buffer.
+        size = getattr(self.raw, 'readinto1', self.raw.readinto)(buffer)
+        if size:
+            self.bytes += size
+        return size
+
+    def seekable(self):
+        return self.raw.seekable()
+
+    def seek(self, offset, whence=io.SEEK_SET):
+        position = self.raw.seek(offset, whence)
+        self.bytes = position
+        return position
+
+    def tell(self):
+        return self.raw.tell()
+
+class OffsetReader(object):
+    def __init__(self, raw, encoding):
+        if codecs.lookup(encoding).name.startswith(('utf-16', 'utf-32')):
+            die('--build-index and --lookup need an ASCII-compatible input encoding, not %s' % encoding, EXIT_CONFIG)
+        self.raw = raw
+        self.offset = 0
+        self.encoding = encoding
+        self.layout = byteLayout(raw, encoding)
+
+    def __iter__(self):
+        for line in self.raw:
+            self.offset += len(line)
+            yield line.decode(self.encoding)
+
+    def seekable(self):
+        return self.raw.seekable()
+
+    def seek(self, offset):
+        self.offset = self.raw.seek(offset)
+
+class Base64Reader(io.RawIOBase):
+    def __init__(self, raw):
+        self.raw = raw
+        self.pending = b''
+
+    def readable(self):
+        return True
+
+    def readinto(self, buffer):
+        data = b''
+        while not data:
+            chunk = self.raw.read1(max(len(buffer) // 3 * 4, 4))
+            if not chunk:
+                if self.pending:
+                    die('truncated base64 input', EXIT_PARSE)
+                return 0
+            encoded = self.pending + b''.join(chunk.split())
+            usable = len(encoded) - len(encoded) % 4
+            self.pending = encoded[usable:]
+            try:
+                data = base64.b64decode(encoded[:usable], validate=True)
+            except binascii.Error:
+                die('invalid base64 input', EXIT_PARSE)
+        buffer[:len(data)] = data
+        return len(data)
+
+def waitForInput(path, timeout):
+    deadline = time.time() + timeout
+    while not os.path.exists(path):
+        remaining = deadline - time.time()
+        if remaining <= 0:
+            die("timed out after %gs waiting for '%s'" % (timeout, path), EXIT_IO)
+        time.sleep(min(0.5, remaining))
+
+class ConcatReader(object):
+    def __init__(self, sources, args):
+        self.sources = sources
+        self.args = args
+        self.headerRows = 0 if args.no_headers else args.header_rows
+        self.current = None
+
+    def headerKey(self, row, text):
+        if self.args.trim in ('headers', 'all'):
+            row = trimRow(row, text, self.args.delimiter, None if self.args.no_quoting else self.args.quote, self.args.escape)
+        if self.args.trim_header_names:
+            row = [name.strip() for name in row]
+        return row
+
+    def __iter__(self):
+        expected = None
+        comment = self.args.comment_char
+        for name, lines in self.sources:
+            self.current = name
+            lines = iter(lines)
+            consumed = []
+            record = []
+            def tap():
+                for line in lines:
+                    consumed.append(line)
+                    if comment is not None and not record and line.startswith(comment):
+                        continue
+                    record.append(line)
+                    yield line
+            reader = csv.reader(tap(), **readerOptions(self.args))
+            header = []
+            for number in range(self.headerRows):
+                del record[:]
+                header.append(self.headerKey(next(reader, []), ''.join(record)))
+            if expected is None:
+                expected = header
+                for line in consumed:
+                    yield line
+            elif header != expected:
+                die("header of '%s' does not match the first input" % name, EXIT_PARSE)
+            for line in lines:
+                yield line
+
+    def seekable(self):
+        return False
+
+def inputEncoding(raw, args):
+    if args.encoding is None or codecs.lookup(args.encoding).name.startswith('utf-16'):
+        head = raw.peek(4)[:4]
+        if head.startswith((codecs.BOM_UTF16_LE, codecs.BOM_UTF16_BE)):
+            return 'utf-16'
+    if args.encoding is None:
+        if raw.peek(3)[:3] == codecs.BOM_UTF8:
+            return 'utf-8-sig'
+        return locale.getpreferredencoding(False)
+    if codecs.lookup(args.encoding).name == 'utf-8':
+        return 'utf-8-sig'
+    return args.encoding
+
+def byteLayout(raw, encoding):
+    name = codecs.lookup(encoding).name
+    if name == 'utf-8-sig':
+        return 'utf-8', 3 if raw.peek(3)[:3] == codecs.BOM_UTF8 else 0
+    for family, size, little, big in (('utf-16', 2, codecs.BOM_UTF16_LE, codecs.BOM_UTF16_BE), ('utf-32', 4, codecs.BOM_UTF32_LE, codecs.BOM_UTF32_BE)):
+        if name == family:
+            head = raw.peek(size)[:size]
+            if head == big:
+                return family + '-be', size
+            if head == little:
+                return family + '-le', size
+            return '%s-%s' % (family, 'le' if sys.byteorder == 'little' else 'be'), 0
+    return encoding, 0
+
+def textInput(raw, args):
+    encoding = inputEncoding(raw, args)
+    text = io.TextIOWrapper(raw, encoding=encoding, newline='')
+    # The codec to measure lines with, and the BOM bytes the decoder drops.
+    text.layout = byteLayout(raw, encoding)
+    return text
+
+def zipSources(raw, member, args):
+    if not raw.seekable():
+        spool = tempfile.TemporaryFile()
+        shutil.copyfileobj(raw, spool)
+        raw = spool
+    try:
+        archive = zipfile.ZipFile(raw)
+    except zipfile.BadZipFile as e:
+        die('invalid zip archive: %s' % e, EXIT_PARSE)
+    if member is not None:
+        names = [member]
+        if member not in archive.namelist():
+            die('no such zip member: %s' % member, EXIT_CONFIG)
     else:
-        for row in reader:
-            line = []
-            for index, col in enumerate(row):
-                for field in fields:
-                    if index == int(field) - 1:
-                        line.append(col)
+        names = [name for name in archive.namelist() if name.lower().endswith('.csv')]
+    sources = []
+    for name in names:
+        member = archive.open(name)
+        sources.append((name, textInput(member, args)))
+    return sources
+
+def readerOptions(args):
+    options = {'delimiter': args.delimiter, 'quotechar': args.quote, 'escapechar': args.escape, 'doublequote': args.escape is None}
+    if args.no_quoting:
+        options['quoting'] = csv.QUOTE_NONE
+    return options
+
+def writerOptions(args):
+    return {'delimiter': args.output_delimiter or args.delimiter, 'quotechar': args.quote, 'escapechar': args.escape, 'doublequote': args.escape is None}
+
+class ByteTotal(object):
+    def __init__(self, counters):
+        self.counters = counters
+
+    @property
+    def bytes(self):
+        return sum(counter.bytes for counter in self.counters)
+
+def isUrl(path):
+    return path is not None and re.match(r'https?://', path, re.IGNORECASE) is not None
+
+def openUrl(url, timeout):
+    request = urllib.request.Request(url, headers={'User-Agent': 'csvparser'})
+    try:
+        return urllib.request.urlopen(request, timeout=timeout)
+    except urllib.error.HTTPError as e:
+        die("can't fetch '%s': HTTP %d %s" % (url, e.code, e.reason), EXIT_IO)
+    except urllib.error.URLError as e:
+        die("can't fetch '%s': %s" % (url, e.reason), EXIT_IO)
+    except (OSError, http.client.HTTPException) as e:
+        die("can't fetch '%s': %s" % (url, e), EXIT_IO)
+
+def openRaw(path, args):
+    if path == '-':
+        path = None
+    if isUrl(path):
+        counter = ByteCounter(openUrl(path, args.timeout))
+        raw = io.BufferedReader(counter)
+        if args.base64_input:
+            raw = io.BufferedReader(Base64Reader(raw))
+        if args.input_compression == 'gzip' or (args.input_compression is None and urllib.parse.urlsplit(path).path.lower().endswith('.gz')):
+            raw = gzip.GzipFile(fileobj=raw, mode='rb')
+        return raw, counter
+    if path is not None and args.wait_for_input:
+        waitForInput(path, args.wait_for_input)
+    try:
+        buffering = -1
+        if path is not None and args.auto_buffer:
+            buffering = bufferSize(os.path.getsize(path), args.max_memory)
+            sys.stderr.write('read buffer: %d bytes\n' % buffering)
+        raw = sys.stdin.buffer if path is None else open(path, 'rb', buffering=0)
+    except OSError as e:
+        die("can't open '%s': %s" % (path, e.strerror), EXIT_IO)
+    counter = ByteCounter(raw)
+    raw = io.BufferedReader(counter, buffering if buffering > 0 else io.DEFAULT_BUFFER_SIZE)
+    if args.base64_input:
+        raw = io.BufferedReader(Base64Reader(raw))
+    if args.input_compression == 'gzip' or (args.input_compression is None and path is not None and path.lower().endswith('.gz')):
+        raw = gzip.GzipFile(fileobj=raw, mode='rb')
+    return raw, counter
+
+def openInput(paths, args):
+    if len(paths) > 1:
+        counters = []
+        def sources():
+            for path in paths:
+                raw, counter = openRaw(path, args)
+                counters.append(counter)
+                yield path, textInput(raw, args)
+        return ConcatReader(sources(), args), ByteTotal(counters)
+    path = paths[0] if paths and paths[0] != '-' else None
+    raw, counter = openRaw(path, args)
+    if args.zip or args.zip_member is not None or (path is not None and not isUrl(path) and path.lower().endswith('.zip')):
+        return ConcatReader(zipSources(raw, args.zip_member, args), args), counter
+    if args.build_index:
+        return OffsetReader(raw, inputEncoding(raw, args)), counter
+    return textInput(raw, args), counter
+
+class BgzfWriter(io.RawIOBase):
+    blockSize = 0xff00
+    eof = bytes.fromhex('1f8b08040000000000ff0600424302001b0003000000000000000000')
+
+    def __init__(self, raw, indexPath):
+        self.raw = raw
+        self.indexPath = indexPath
+        self.pending = bytearray()
+        self.offsets = []
+        self.compressed = 0
+        self.uncompressed = 0
+
+    def writable(self):
+        return True
+
+    def write(self, data):
+        self.pending += data
+        while len(self.pending) >= self.blockSize:
+            self.writeBlock(bytes(self.pending[:self.blockSize]))
+            del self.pending[:self.blockSize]
+        return len(data)
+
+    def writeBlock(self, data):
+        compressor = zlib.compressobj(zlib.Z_DEFAULT_COMPRESSION, zlib.DEFLATED, -15)
+        cdata = compressor.compress(data) + compressor.flush()
+        header = struct.pack('<BBBBIBBHBBHH', 31, 139, 8, 4, 0, 0, 255, 6, 66, 67, 2, len(cdata) + 25)
+        block = header + cdata + struct.pack('<II', zlib.crc32(data), len(data))
+        self.raw.write(block)
+        self.compressed += len(block)
+        self.uncompressed += len(data)
+        self.offsets.append((self.compressed, self.uncompressed))
+
+    def close(self):
+        if not self.closed:
+            if self.pending:
+                self.writeBlock(bytes(self.pending))
+            self.raw.write(self.eof)
+            self.raw.close()
+            if self.indexPath:
+                with open(self.indexPath, 'wb') as index:
+                    index.write(struct.pack('<Q', len(self.offsets)))
+                    for compressed, uncompressed in self.offsets:
+                        index.write(struct.pack('<QQ', compressed, uncompressed))
+        super(BgzfWriter, self).close()
+
+class CompressedWriter(io.RawIOBase):
+    def __init__(self, raw, compression, owned):
+        self.raw = raw
+        self.owned = owned
+        if compression == 'gzip':
+            self.encoder = gzip.GzipFile(fileobj=raw, mode='wb')
+        else:
+            try:
+                import zstandard
+            except ImportError:
+                die('zstd output requires the zstandard module', EXIT_CONFIG)
+            self.encoder = zstandard.ZstdCompressor().stream_writer(raw, closefd=False)
+
+    def writable(self):
+        return True
+
+    def write(self, data):
+        self.encoder.write(data)
+        return len(data)
+
+    def close(self):
+        if not self.closed:
+            self.encoder.close()
+            if self.owned:
+                self.raw.close()
+            else:
+                self.raw.flush()
+        super(CompressedWriter, self).close()
+
+def openOutput(path, args):
+    if path is None:
+        if args.output_compression in (None, 'none'):
+            return sys.stdout, None
+        raw = io.BufferedWriter(CompressedWriter(sys.stdout.buffer, args.output_compression, False))
+        return io.TextIOWrapper(raw, newline=''), None
+    try:
+        if os.path.exists(path) and not os.path.isfile(path):
+            raw, tmp = open(path, 'wb'), None
+        else:
+            path = os.path.abspath(path)
+            fd, tmp = tempfile.mkstemp(dir=os.path.dirname(path), prefix='.%s.' % os.path.basename(path))
+            umask = os.umask(0)
+            os.umask(umask)
+            os.chmod(tmp, 0o666 & ~umask)
+            raw = os.fdopen(fd, 'wb')
+    except OSError as e:
+        die("can't open '%s': %s" % (path, e.strerror), EXIT_IO)
+    if args.bgzip_output:
+        raw = io.BufferedWriter(BgzfWriter(raw, tmp and tmp + '.gzi'))
+    elif args.output_compression not in (None, 'none'):
+        raw = io.BufferedWriter(CompressedWriter(raw, args.output_compression, True))
+    return io.TextIOWrapper(raw, newline=''), tmp
+
+def columnIndex(header, column, base):
+    if column.isdigit() and int(column) >= base:
+        return int(column) - base
+    try:
+        return header.index(column)
+    except ValueError:
+        die('no such column: %s' % column, EXIT_CONFIG)
+
+def quotedFields(text, delimiter, quote, escape):
+    quoted = []
+    position = 0
+    while True:
+        while position < len(text) and text[position] in ' \t' and text[position] != delimiter:
+            position += 1
+        if position < len(text) and text[position] == quote:
+            start = position = position + 1
+            while position < len(text):
+                if text[position] == escape:
+                    position += 1
+                elif text[position] == quote:
+                    if escape is not None or text[position + 1:position + 2] != quote:
+                        break
+                    position += 1
+                position += 1
+            value = text[start:position]
+            if escape is None:
+                value = value.replace(quote * 2, quote)
+            else:
+                value = re.sub(re.escape(escape) + '(.)', r'\1', value, flags=re.S)
+            quoted.append(value)
+        else:
+            quoted.append(None)
+        while position < len(text) and text[position] not in (delimiter, '\r', '\n'):
+            position += 1
+        if position >= len(text) or text[position] != delimiter:
+            return quoted
+        position += 1
+
+def trimRow(row, text, delimiter, quote, escape):
+    quoted = quotedFields(text, delimiter, quote, escape)
+    return [value.strip() if index >= len(quoted) or quoted[index] is None else quoted[index] for index, value in enumerate(row)]
+
+class FixedWidthReader(object):
+    def __init__(self, lines, widths, keepSpaces, pad):
+        self.lines = lines
+        self.widths = widths
+        self.keepSpaces = keepSpaces
+        self.pad = pad
+        self.line_num = 0
+
+    def __iter__(self):
+        return self
+
+    def __next__(self):
+        line = next(self.lines).rstrip('\r\n')
+        self.line_num += 1
+        total = sum(self.widths)
+        if len(line) < total:
+            if not self.pad:
+                raise csv.Error('line is %d characters long, expected %d' % (len(line), total))
+            line = line.ljust(total)
+        row = []
+        start = 0
+        for width in self.widths:
+            field = line[start:start + width]
+            row.append(field if self.keepSpaces else field.rstrip(' '))
+            start += width
+        return row
+
+class Records(object):
+    def __init__(self, lines, args, stats, validator, reject, layout):
+        self.pending = []
+        self.comment = args.comment_char
+        self.skipped = 0
+        self.encoding, self.consumed = layout
+        self.byte = 0
+        if args.fixed_width:
+            self.reader = FixedWidthReader(self.track(lines), args.fixed_width, args.fixed_width_keep_spaces, args.fixed_width_pad)
+        else:
+            self.reader = csv.reader(self.track(lines), **readerOptions(args))
+        self.mode = args.on_error
+        self.dryValidate = args.dry_validate
+        self.stats = stats
+        self.validator = validator
+        self.reject = reject
+        self.width = None
+        self.delimiter = args.delimiter
+        self.quote = None if args.no_quoting else args.quote
+        self.escape = args.escape
+        self.headerRows = 0 if args.no_headers else args.header_rows
+        self.trimHeaders = args.trim in ('headers', 'all')
+        self.trimFields = args.trim in ('fields', 'all')
+
+    @property
+    def line_num(self):
+        return self.reader.line_num + self.skipped
+
+    def track(self, lines):
+        for line in lines:
+            size = len(line.encode(self.encoding, 'replace'))
+            if self.comment is not None and not self.pending and line.startswith(self.comment):
+                self.skipped += 1
+                self.consumed += size
+                continue
+            if not self.pending:
+                self.byte = self.consumed
+            self.consumed += size
+            self.pending.append(line)
+            yield line
+
+    @property
+    def start(self):
+        return self.line_num - len(self.pending) + 1
+
+    def rejectRecord(self, number, error):
+        if self.reject is not None:
+            self.reject.writerow([self.start, ''.join(self.pending).rstrip('\r\n'), error])
+            self.stats.rejected += 1
+            return
+        message = 'row %d (line %d, byte %d): %s' % (number, self.start, self.byte, error)
+        if self.dryValidate:
+            self.validator.fail(message)
+            return
+        if self.mode == 'fail':
+            die(message, EXIT_PARSE)
+        if self.mode == 'collect':
+            self.validator.problems.append(message)
+        else:
+            sys.stderr.write('skipping %s\n' % message)
+
+    def __iter__(self):
+        number = 0
+        while True:
+            del self.pending[:]
+            try:
+                row = next(self.reader)
+            except StopIteration:
+                return
+            except csv.Error as e:
+                number += 1
+                self.rejectRecord(number, str(e))
+                continue
+            number += 1
+            if self.headerRows:
+                self.headerRows -= 1
+                if self.trimHeaders:
+                    row = trimRow(row, ''.join(self.pending), self.delimiter, self.quote, self.escape)
+            elif self.trimFields:
+                row = trimRow(row, ''.join(self.pending), self.delimiter, self.quote, self.escape)
+            if self.width is not None and len(row) != self.width:
+                self.rejectRecord(number, 'expected %d fields, got %d' % (self.width, len(row)))
+                continue
+            yield row
+
+def detectDelimiter(sample):
+    found = []
+    for candidate in (',', '\t', ';', '|'):
+        try:
+            widths = set(len(row) for row in csv.reader(sample, delimiter=candidate) if row)
+        except csv.Error:
+            continue
+        if len(widths) == 1 and widths.pop() > 1:
+            found.append(candidate)
+    if len(found) != 1:
+        die('cannot detect the delimiter (%s); give it with -d' % ('candidates: %s' % ' '.join(repr(c) for c in found) if found else 'no consistent candidate'), EXIT_CONFIG)
+    return found[0]
+
+def untilInterrupted(rows):
+    try:
+        for row in rows:
+            yield row
+            if interrupted.is_set():
+                return
+    except KeyboardInterrupt:
+        # Raised by a second Ctrl-C while waiting for input; ending the rows
+        # here lets buffered output such as a table or Avro block be written.
+        interrupted.set()
+
+def hasNewline(row):
+    return any('\n' in field or '\r' in field for field in row)
+
+class Validator(object):
+    def __init__(self, collect, status):
+        self.collect = collect
+        self.status = status
+        self.problems = []
+
+    def fail(self, message):
+        if not self.collect:
+            die(message, self.status)
+        self.problems.append(message)
+
+def checkNewlines(rows, reader, validator):
+    for number, row in enumerate(rows, 1):
+        if hasNewline(row):
+            validator.fail('record %d (line %d) has a newline in a field' % (number, reader.start))
+        yield row
+
+def checkMonotonic(rows, reader, index, strict, validator):
+    previous = None
+    for row in rows:
+        value = row[index] if index < len(row) else ''
+        key = parseNumber(value)
+        if key is None:
+            key = parseDate(value)
+        if key is None:
+            validator.fail('line %d: %r is not a number or date' % (reader.start, value))
+        elif previous is not None and type(key) is not type(previous):
+            validator.fail('line %d: %r mixes numbers and dates' % (reader.start, value))
+        else:
+            if previous is not None and key < previous:
+                validator.fail('line %d: %r is less than the previous value' % (reader.start, value))
+            elif strict and key == previous:
+                validator.fail('line %d: %r repeats the previous value' % (reader.start, value))
+            previous = key
+        yield row
+
+def readSchemaOrder(path):
+    try:
+        with open(path) as orderfile:
+            return [line.rstrip('\r\n') for line in orderfile if line.strip()]
+    except OSError as e:
+        die("can't open '%s': %s" % (path, e.strerror), EXIT_IO)
+
+def schemaMapping(header, order, dropExtra):
+    mapping = [header.index(name) if name in header else None for name in order]
+    if not dropExtra:
+        mapping += [index for index, name in enumerate(header) if name not in order]
+    return mapping
+
+def reorderRow(row, mapping):
+    return [row[index] if index is not None and index < len(row) else '' for index in mapping]
+
+def headTail(rows, size):
+    tail = collections.deque(maxlen=size)
+    for number, row in enumerate(rows):
+        if number < size:
+            yield row
+        else:
+            tail.append(row)
+    for row in tail:
+        yield row
+
+def fillMissing(rows, width, sentinel):
+    for row in rows:
+        if len(row) < width:
+            row = row + [sentinel] * (width - len(row))
+        yield row
+
+def samplePerGroup(rows, index, size, seed, limit):
+    rng = random.Random(seed)
+    groups = {}
+    for number, row in enumerate(rows):
+        if number % 1000 == 0:
+            checkMemory(limit, '--sample-per-group')
+        key = row[index] if index < len(row) else ''
+        seen, reservoir = groups.setdefault(key, [0, []])
+        groups[key][0] = seen + 1
+        if len(reservoir) < size:
+            reservoir.append((number, row))
+        else:
+            slot = rng.randrange(seen + 1)
+            if slot < size:
+                reservoir[slot] = (number, row)
+    sample = [item for seen, reservoir in groups.values() for item in reservoir]
+    for number, row in sorted(sample, key=lambda item: item[0]):
+        yield row
+
+class SeenKeys(object):
+    def __init__(self, limit):
+        self.keys = set()
+        self.limit = limit
+        self.db = None
+
+    def add(self, row):
+        key = int.from_bytes(hashlib.blake2b(json.dumps(row).encode('utf-8'), digest_size=8).digest(), 'big', signed=True)
+        if self.db is not None:
+            return self.db.execute('insert or ignore into seen values (?)', (key,)).rowcount == 1
+        if key in self.keys:
+            return False
+        self.keys.add(key)
+        if self.limit and len(self.keys) % 100000 == 0 and (peakMemory() or 0) > self.limit:
+            self.db = sqlite3.connect('')
+            self.db.execute('create table seen (key integer primary key)')
+            self.db.executemany('insert into seen values (?)', ((key,) for key in self.keys))
+            self.keys = set()
+        return True
+
+def dedupeRows(rows, indices, stats, limit):
+    seen = SeenKeys(limit)
+    for row in rows:
+        if seen.add(selectFields(row, indices)):
+            yield row
+        else:
+            stats.duplicates += 1
+
+def fieldIndices(fields, base, width, checked):
+    if fields == 0:
+        return None
+    indices = []
+    for field in fields:
+        low, dash, high = field.partition('-')
+        if not low.isdigit() or high and not high.isdigit():
+            die('invalid field: %s' % field, EXIT_CONFIG)
+        if int(low) < base:
+            die('field indices are 1-based; 0 is invalid', EXIT_CONFIG)
+        if checked and int(low) - base >= width:
+            die('field %s is beyond the %d columns of the header' % (field, width), EXIT_CONFIG)
+        if not dash:
+            indices.append(int(low) - base)
+            continue
+        last = int(high) if high else width + base - 1
+        if high and last < int(low):
+            die('invalid field range: %s' % field, EXIT_CONFIG)
+        indices.extend(range(int(low) - base, last - base + 1))
+    return indices
+
+def selectedIndices(header, fields, args):
+    if args.exclude is not None:
+        excluded = set(columnIndex(header, column, args.index_base) for column in args.exclude.split(','))
+        return [index for index in range(len(header)) if index not in excluded]
+    if args.columns is None:
+        return fieldIndices(fields, args.index_base, len(header), not args.no_headers)
+    indices = []
+    for name in args.columns.split(','):
+        if name not in header:
+            die('no such column: %s' % name, EXIT_CONFIG)
+        indices.append(header.index(name))
+    return indices
+
+def selectFields(row, indices):
+    if indices is None:
+        return row
+    return [row[index] for index in indices if index < len(row)]
+
+def selectRows(rows, indices):
+    for row in rows:
+        yield selectFields(row, indices)
+
+def splitRetention(header, rows, indices, stats, args):
+    column, cutoff = args.retention
+    index = columnIndex(header, column, args.index_base)
+    with open(args.old_out, 'w', newline='') as old, open(args.current_out, 'w', newline='') as current:
+        writers = {'old': csv.writer(old, **writerOptions(args)), 'current': csv.writer(current, **writerOptions(args))}
+        if not args.no_headers:
+            for writer in writers.values():
+                writer.writerow(outputLine(header, indices, args))
+        for row in rows:
+            date = parseDate(row[index]) if index < len(row) else None
+            if date is None:
+                bucket = args.retention_unparseable
+            else:
+                bucket = 'old' if date < cutoff else 'current'
+            if bucket != 'drop':
+                writers[bucket].writerow(outputLine(row, indices, args))
+                stats.count()
+
+def partPath(path, number):
+    base, ext = os.path.splitext(path)
+    return '%s.part%05d%s' % (base, number, ext)
+
+def splitParts(header, rows, indices, stats, args):
+    buffer = io.StringIO()
+    formatter = csv.writer(buffer, **writerOptions(args))
+    def encode(row):
+        buffer.seek(0)
+        buffer.truncate()
+        formatter.writerow(outputLine(row, indices, args))
+        return buffer.getvalue()
+    headerLine = '' if args.no_headers else encode(header)
+    part = None
+    number = 0
+    try:
+        for row in rows:
+            line = encode(row)
+            if part is not None:
+                full = args.split_rows and count >= args.split_rows
+                full = full or args.split_bytes and size + len(line.encode(part.encoding)) > args.split_bytes
+                if full:
+                    part.close()
+                    part = None
+            if part is None:
+                number += 1
+                part = open(partPath(args.output, number), 'w', newline='')
+                part.write(headerLine)
+                size = len(headerLine.encode(part.encoding))
+                count = 0
+            part.write(line)
+            size += len(line.encode(part.encoding))
+            count += 1
+            stats.count()
+    finally:
+        if part is not None:
+            part.close()
+
+def groupPath(path, value, number=1):
+    if '/' in value or '\\' in value or value in ('.', '..'):
+        die('unsafe --split-by value for a file name: %r' % value, EXIT_IO)
+    base, ext = os.path.splitext(path)
+    name = re.sub(r'[^\w.-]', '_', value) or '_'
+    return '%s_%s%s' % (base, name if number == 1 else '%s_%d' % (name, number), ext)
+
+class GroupPaths(object):
+    def __init__(self, output):
+        self.output = output
+        self.paths = {}
+        self.owners = {}
+
+    def __call__(self, value):
+        path = self.paths.get(value)
+        if path is None:
+            number = 1
+            path = groupPath(self.output, value)
+            # Distinct values can clean up to the same name, or differ only
+            # in case on a case-insensitive file system.
+            while path.lower() in self.owners:
+                number += 1
+                path = groupPath(self.output, value, number)
+            if number > 1:
+                sys.stderr.write('--split-by value %r has the same file name as %r; writing it to %s\n' % (value, self.owners[groupPath(self.output, value).lower()], path))
+            self.paths[value] = path
+            self.owners[path.lower()] = value
+        return path
+
+def splitGroups(header, rows, indices, stats, args):
+    index = columnIndex(header, args.split_by, args.index_base)
+    handles = collections.OrderedDict()
+    created = set()
+    paths = GroupPaths(args.output)
+    try:
+        for row in rows:
+            path = paths(row[index] if index < len(row) else '')
+            if path in handles:
+                handles.move_to_end(path)
+            else:
+                if len(handles) >= args.split_max_open:
+                    handles.popitem(last=False)[1][0].close()
+                handle = open(path, 'a' if path in created else 'w', newline='')
+                handles[path] = (handle, csv.writer(handle, **writerOptions(args)))
+                if path not in created:
+                    created.add(path)
+                    if not args.no_headers:
+                        handles[path][1].writerow(outputLine(header, indices, args))
+            handles[path][1].writerow(outputLine(row, indices, args))
+            stats.count()
+    finally:
+        for handle, writer in handles.values():
+            handle.close()
+
+def formatNumber(value):
+    return '%.15g' % value
+
+def parseNumber(value):
+    try:
+        return float(value)
+    except ValueError:
+        return None
+
+def replaceValues(rows, replacements, patterns, indices):
+    for row in rows:
+        row = list(row)
+        for index in range(len(row)) if indices is None else indices:
+            if index >= len(row):
+                continue
+            value = row[index]
+            for old, new in replacements:
+                value = value.replace(old, new)
+            for pattern, repl in patterns:
+                value = pattern.sub(repl, value)
+            row[index] = value
+        yield row
+
+def matches(value, op, operand):
+    if op == '~=':
+        return operand in value
+    left, right = parseNumber(value), parseNumber(operand)
+    if left is None or right is None:
+        left, right = value, operand
+    if op == '==':
+        return left == right
+    if op == '!=':
+        return left != right
+    if op == '>':
+        return left > right
+    if op == '<':
+        return left < right
+    if op == '>=':
+        return left >= right
+    return left <= right
+
+def filterRows(rows, index, op, operand):
+    for row in rows:
+        if matches(row[index] if index < len(row) else '', op, operand):
+            yield row
+
+def addConstants(rows, values, source):
+    for number, row in enumerate(rows, 1):
+        extra = []
+        for value in values:
+            if '{' in value:
+                value = value.replace('{filename}', source()).replace('{rownum}', str(number))
+                value = value.replace('{timestamp}', datetime.datetime.now().isoformat(timespec='seconds'))
+            extra.append(value)
+        yield row + extra
+
+def addRunningTotal(rows, index, group):
+    total = 0.0
+    previous = None
+    for row in rows:
+        if group is not None:
+            key = row[group] if group < len(row) else ''
+            if key != previous:
+                total = 0.0
+                previous = key
+        value = parseNumber(row[index]) if index < len(row) else None
+        total += value or 0.0
+        yield row + [formatNumber(total)]
+
+def indexRows(rows, reader, index, writer):
+    for row in rows:
+        if index < len(row):
+            writer.writerow([row[index], reader.byte])
+        yield row
+
+def avroLong(value):
+    value = (value << 1) ^ (value >> 63)
+    encoded = bytearray()
+    while value & ~0x7f:
+        encoded.append((value & 0x7f) | 0x80)
+        value >>= 7
+    encoded.append(value)
+    return bytes(encoded)
+
+def avroBytes(data):
+    return avroLong(len(data)) + data
+
+def avroNames(header):
+    names = []
+    for name in header:
+        name = re.sub(r'\W', '_', name, flags=re.ASCII)
+        if not name or name[0].isdigit():
+            name = '_' + name
+        unique = name
+        while unique in names:
+            unique = '%s_%d' % (name, len(names))
+        names.append(unique)
+    return names
+
+def avroValue(kind, value):
+    if kind == 'integer':
+        number = int(value)
+        if not -2 ** 63 <= number < 2 ** 63:
+            raise ValueError(value)
+        return avroLong(number)
+    if kind == 'number':
+        return struct.pack('<d', float(value))
+    if kind == 'boolean':
+        if value.lower() not in ('true', 'false'):
+            raise ValueError(value)
+        return b'\x01' if value.lower() == 'true' else b'\x00'
+    return avroBytes(value.encode('utf-8'))
+
+def writeAvro(header, rows, outfile, stats, args):
+    avroTypes = {'integer': 'long', 'number': 'double', 'boolean': 'boolean'}
+    types, rows = inferTypes(header, rows, args.infer_rows)
+    schema = {'type': 'record', 'name': 'Row', 'fields': [
+        {'name': name, 'type': ['null', avroTypes.get(kind, 'string')], 'default': None}
+        for name, (kind, null) in zip(avroNames(header), types)]}
+    sync = os.urandom(16)
+    outfile.flush()
+    out = outfile.buffer
+    out.write(b'Obj\x01' + avroLong(2))
+    out.write(avroBytes(b'avro.schema') + avroBytes(json.dumps(schema).encode('utf-8')))
+    out.write(avroBytes(b'avro.codec') + avroBytes(b'null') + avroLong(0) + sync)
+    block = bytearray()
+    count = 0
+    for line in rows:
+        for index, (kind, null) in enumerate(types):
+            value = line[index] if index < len(line) else ''
+            if value == '':
+                block += avroLong(0)
+                continue
+            try:
+                block += avroLong(1) + avroValue(kind, value)
+            except ValueError:
+                die('record %d: %r in column %s does not match the inferred type %s' % (stats.records + 1, value, header[index], kind), EXIT_PARSE)
+        count += 1
+        stats.count()
+        if count >= 1000 or len(block) >= 65536:
+            out.write(avroLong(count) + avroBytes(bytes(block)) + sync)
+            block = bytearray()
+            count = 0
+    if count:
+        out.write(avroLong(count) + avroBytes(bytes(block)) + sync)
+
+def outputLine(row, indices, args):
+    line = selectFields(row, indices)
+    if args.field_separator_in_output is not None:
+        line = [args.field_separator_in_output.join(line)]
+    if args.min_columns and len(line) < args.min_columns:
+        line = line + [''] * (args.min_columns - len(line))
+    return line
+
+def displayWidth(text):
+    return sum(2 if unicodedata.east_asian_width(char) in 'WF' else 1 for char in text)
+
+def tableCell(value, limit):
+    value = value.replace('\r', '\\r').replace('\n', '\\n').replace('\t', ' ')
+    if displayWidth(value) <= limit:
+        return value
+    cell = ''
+    for char in value:
+        if displayWidth(cell + char) > limit - 1:
+            break
+        cell += char
+    return cell + '\u2026'
+
+def writeTable(header, rows, outfile, stats, args):
+    lines = list(itertools.islice(rows, args.table_rows + 1))
+    truncated = len(lines) > args.table_rows
+    lines = [[tableCell(value, args.max_col_width) for value in line] for line in lines[:args.table_rows]]
+    names = [] if args.no_headers else [tableCell(value, args.max_col_width) for value in header]
+    count = max([len(names)] + [len(line) for line in lines])
+    widths = [0] * count
+    for line in [names] + lines:
+        for index, value in enumerate(line):
+            widths[index] = max(widths[index], displayWidth(value))
+
+    def rule(left, middle, right):
+        return left + middle.join('\u2500' * (width + 2) for width in widths) + right + '\n'
+
+    def cells(line):
+        line = line + [''] * (count - len(line))
+        return '\u2502' + '\u2502'.join(' ' + value + ' ' * (width - displayWidth(value)) + ' ' for value, width in zip(line, widths)) + '\u2502\n'
+
+    outfile.write(rule('\u250c', '\u252c', '\u2510'))
+    if names:
+        outfile.write(cells(names))
+        outfile.write(rule('\u251c', '\u253c', '\u2524'))
+    for line in lines:
+        outfile.write(cells(line))
+        stats.count()
+    outfile.write(rule('\u2514', '\u2534', '\u2518'))
+    if truncated:
+        outfile.write('(showing the first %d rows; raise --table-rows to see more)\n' % args.table_rows)
+
+def writeRows(header, rows, indices, outfile, stats, args):
+    if args.output_format == 'table':
+        writeTable(outputLine(header, indices, args), (outputLine(row, indices, args) for row in rows), outfile, stats, args)
+        return
+    if args.output_format == 'avro':
+        writeAvro(outputLine(header, indices, args), (outputLine(row, indices, args) for row in rows), outfile, stats, args)
+        return
+    if args.output_format == 'jsonl':
+        names = outputLine(header, indices, args)
+        repeated = [name for name, count in collections.Counter(names).items() if count > 1]
+        if repeated and not args.no_headers:
+            die('duplicate column names in jsonl output: %s; see --columns-dedup or --rename' % ', '.join(repeated), EXIT_CONFIG)
+        for row in rows:
+            line = outputLine(row, indices, args)
+            if not args.no_headers:
+                line = dict(zip(names, line))
+            outfile.write(json.dumps(line, ensure_ascii=False) + '\n')
+            if args.line_buffered:
+                outfile.flush()
+            stats.count()
+        return
+    writer = csv.writer(outfile, **writerOptions(args))
+    if args.output_format == 'null-delimited':
+        if args.field_separator_in_output is None and (indices is None or len(indices) != 1):
+            die('null-delimited output needs exactly one field', EXIT_CONFIG)
+    elif not args.no_headers:
+        writer.writerow(outputLine(header, indices, args))
+    for row in rows:
+        line = outputLine(row, indices, args)
+        if args.output_format == 'null-delimited':
+            outfile.write(''.join(line) + '\0')
+        else:
             writer.writerow(line)
+        if args.line_buffered:
+            outfile.flush()
+        stats.count()
+
+def lookupRecords(outfile, fields, args):
+    # The index is a key,offset CSV in input order. One sequential pass over
+    # it, keeping only the wanted keys, is cheap next to scanning the data and
+    # needs no memory per indexed key; each match is then a single seek.
+    wanted = set(args.key)
+    offsets = {}
+    try:
+        with open(args.lookup, newline='') as indexfile:
+            reader = csv.reader(indexfile)
+            next(reader, None)
+            for key, offset in reader:
+                if key in wanted:
+                    offsets.setdefault(key, []).append(int(offset))
+    except OSError as e:
+        die("can't open '%s': %s" % (args.lookup, e.strerror), EXIT_IO)
+    except ValueError:
+        die('invalid index file: %s' % args.lookup, EXIT_PARSE)
+    writer = csv.writer(outfile, **writerOptions(args))
+    with open(args.infile, 'rb') as raw:
+        infile = OffsetReader(raw, inputEncoding(raw, args))
+        rows = iter(Records(infile, args, Stats(), Validator(False, EXIT_PARSE), None, infile.layout))
+        header = next(rows, [])
+        if args.rename_regex or args.trim_header_names:
+            header = renameHeader(header, args)
+        indices = selectedIndices(header, fields, args)
+        writer.writerow(selectFields(renameColumns(header, args.rename or []), indices))
+        for key in args.key:
+            for offset in offsets.get(key, []):
+                infile.seek(offset)
+                writer.writerow(selectFields(next(rows, []), indices))
+    missing = [key for key in args.key if key not in offsets]
+    for key in missing:
+        sys.stderr.write('key not found: %s\n' % key)
+    return len(missing)
+
+def inferType(value):
+    if value.lower() in ('true', 'false'):
+        return 'boolean'
+    if re.match(r'[-+]?\d+$', value):
+        return 'integer'
+    if re.match(r'[-+]?(\d+\.?\d*|\.\d+)([eE][-+]?\d+)?$', value):
+        return 'number'
+    if parseDate(value) is not None:
+        return 'date' if re.match(r'\d{4}-\d{2}-\d{2}$', value) else 'datetime'
+    return 'string'
+
+def mergeTypes(current, kind):
+    if current is None or current == kind:
+        return kind
+    if set([current, kind]) == set(['integer', 'number']):
+        return 'number'
+    if set([current, kind]) == set(['date', 'datetime']):
+        return 'datetime'
+    return 'string'
+
+def inferTypes(header, rows, size):
+    sample = list(itertools.islice(rows, size))
+    types = [None] * len(header)
+    nullable = [False] * len(header)
+    for row in sample:
+        for index in range(len(header)):
+            value = row[index] if index < len(row) else ''
+            if value == '':
+                nullable[index] = True
+            else:
+                types[index] = mergeTypes(types[index], inferType(value))
+    return [(kind or 'string', null) for kind, null in zip(types, nullable)], itertools.chain(sample, rows)
+
+def typeMatches(kind, wanted):
+    if wanted == 'numeric':
+        return kind in ('integer', 'number')
+    if wanted == 'date':
+        return kind in ('date', 'datetime')
+    return kind == wanted
+
+def writeJsonSchema(path, header, types):
+    jsonTypes = {'integer': 'integer', 'number': 'number', 'boolean': 'boolean'}
+    properties = {}
+    formats = {'date': 'date', 'datetime': 'date-time'}
+    for name, (kind, null) in zip(header, types):
+        jsonType = jsonTypes.get(kind, 'string')
+        properties[name] = {'type': [jsonType, 'null'] if null else jsonType}
+        if kind in formats:
+            properties[name]['format'] = formats[kind]
+    schema = {
+        '$schema': 'http://json-schema.org/draft-07/schema#',
+        'type': 'object',
+        'properties': properties,
+        'required': [name for name, (kind, null) in zip(header, types) if not null],
+    }
+    with open(path, 'w') as schemafile:
+        json.dump(schema, schemafile, indent=2)
+        schemafile.write('\n')
+
+def sortKey(index, numeric):
+    def key(row):
+        value = row[index] if index < len(row) else ''
+        if numeric:
+            number = parseNumber(value)
+            return (0, number, '') if number is not None else (1, 0.0, value)
+        return value
+    return key
+
+def sortRows(rows, index, args):
+    key = sortKey(index, args.numeric)
+    runs = []
+    while True:
+        run = []
+        for row in itertools.islice(rows, args.sort_buffer):
+            if len(run) % 1000 == 0:
+                checkMemory(args.max_memory, '--sort-by')
+            run.append(row)
+        run.sort(key=key, reverse=args.sort_desc)
+        if not run:
+            break
+        if len(run) < args.sort_buffer and not runs:
+            for row in run:
+                yield row
+            return
+        spool = tempfile.TemporaryFile('w+', newline='', dir=args.temp_dir)
+        csv.writer(spool).writerows(run)
+        spool.seek(0)
+        runs.append(spool)
+        if len(run) < args.sort_buffer:
+            break
+    try:
+        for row in heapq.merge(*[csv.reader(spool) for spool in runs], key=key, reverse=args.sort_desc):
+            yield row
+    finally:
+        for spool in runs:
+            spool.close()
+
+def groupAggregates(rows, index, specs, strict):
+    seen = set()
+    current = None
+    values = None
+    def result():
+        out = [current]
+        for (function, column), (count, total, low, high) in zip(specs, values):
+            if function == 'count':
+                out.append(str(count))
+            elif count == 0:
+                out.append('')
+            elif function == 'sum':
+                out.append(formatNumber(total))
+            elif function == 'mean':
+                out.append(formatNumber(total / count))
+            else:
+                out.append(formatNumber(low if function == 'min' else high))
+        return out
+    for row in rows:
+        key = row[index] if index < len(row) else ''
+        if values is None or key != current:
+            if values is not None:
+                yield result()
+                seen.add(current)
+            if key in seen:
+                message = 'group %r appears again; the input is not grouped by the --group-by column' % key
+                if strict:
+                    die(message, EXIT_PARSE)
+                sys.stderr.write('warning: %s\n' % message)
+            current = key
+            values = [[0, 0.0, None, None] for spec in specs]
+        for (function, column), value in zip(specs, values):
+            if column is None:
+                value[0] += 1
+                continue
+            field = row[column] if column < len(row) else ''
+            number = parseNumber(field) if function != 'count' else None
+            if function == 'count':
+                if field != '':
+                    value[0] += 1
+            elif number is not None:
+                value[0] += 1
+                value[1] += number
+                value[2] = number if value[2] is None else min(value[2], number)
+                value[3] = number if value[3] is None else max(value[3], number)
+    if values is not None:
+        yield result()
+
+def dropEmptyColumns(header, rows):
+    spool = tempfile.TemporaryFile('w+', newline='')
+    writer = csv.writer(spool)
+    filled = [False] * len(header)
+    for row in rows:
+        for index, value in enumerate(row[:len(filled)]):
+            if value:
+                filled[index] = True
+        writer.writerow(row)
+    spool.seek(0)
+    keep = [index for index, used in enumerate(filled) if used]
+    return selectFields(header, keep), (selectFields(row, keep) for row in csv.reader(spool))
+
+class ColumnStats(object):
+    def __init__(self, width):
+        self.columns = [[0, 0.0, 0.0, None, None, True] for index in range(width)]
+
+    def update(self, row):
+        for column, value in zip(self.columns, row):
+            if not column[5] or value == '':
+                continue
+            number = parseNumber(value)
+            if number is None:
+                column[5] = False
+                continue
+            column[0] += 1
+            delta = number - column[1]
+            column[1] += delta / column[0]
+            column[2] += delta * (number - column[1])
+            column[3] = number if column[3] is None else min(column[3], number)
+            column[4] = number if column[4] is None else max(column[4], number)
+
+    def write(self, path, header):
+        with open(path, 'w', newline='') as statsfile:
+            writer = csv.writer(statsfile)
+            writer.writerow(['column', 'count', 'min', 'max', 'mean', 'stddev'])
+            for name, (count, mean, m2, low, high, numeric) in zip(header, self.columns):
+                if not numeric or count == 0:
+                    continue
+                stddev = formatNumber((m2 / (count - 1)) ** 0.5) if count > 1 else ''
+                writer.writerow([name, count, formatNumber(low), formatNumber(high), formatNumber(mean), stddev])
+
+class HyperLogLog(object):
+    bits = 12
+
+    def __init__(self):
+        self.registers = bytearray(1 << self.bits)
+
+    def add(self, value):
+        hashed = int.from_bytes(hashlib.blake2b(value.encode('utf-8'), digest_size=8).digest(), 'big')
+        index = hashed >> (64 - self.bits)
+        rest = hashed & ((1 << (64 - self.bits)) - 1)
+        rank = (64 - self.bits) - rest.bit_length() + 1
+        if rank > self.registers[index]:
+            self.registers[index] = rank
+
+    def estimate(self):
+        size = len(self.registers)
+        raw = 0.7213 / (1 + 1.079 / size) * size * size / sum(2.0 ** -register for register in self.registers)
+        zeros = self.registers.count(0)
+        if raw <= 2.5 * size and zeros:
+            return int(round(size * math.log(float(size) / zeros)))
+        return int(round(raw))
+
+class ColumnProfile(object):
+    def __init__(self, width):
+        self.columns = [[0, None, None, HyperLogLog()] for index in range(width)]
+
+    def update(self, row):
+        for column, value in zip(self.columns, row):
+            if value == '':
+                column[0] += 1
+            size = len(value)
+            column[1] = size if column[1] is None else min(column[1], size)
+            column[2] = size if column[2] is None else max(column[2], size)
+            column[3].add(value)
+
+    def summary(self, header):
+        return [collections.OrderedDict([('column', name), ('empty', empty), ('min_len', shortest), ('max_len', longest), ('distinct', distinct.estimate())])
+                for name, (empty, shortest, longest, distinct) in zip(header, self.columns)]
+
+    def write(self, out, header):
+        lines = [['column', 'empty', 'min_len', 'max_len', 'distinct~']]
+        for name, (empty, shortest, longest, distinct) in zip(header, self.columns):
+            lines.append([name, str(empty), '' if shortest is None else str(shortest), '' if longest is None else str(longest), str(distinct.estimate())])
+        widths = [max(len(line[index]) for line in lines) for index in range(len(lines[0]))]
+        for line in lines:
+            out.write('  '.join(value.ljust(width) for value, width in zip(line, widths)).rstrip() + '\n')
+
+class Aggregates(object):
+    def __init__(self, indices):
+        self.indices = indices
+        self.columns = [[0, 0, 0.0, None, None] for index in indices]
+
+    def update(self, row):
+        for column, index in zip(self.columns, self.indices):
+            number = parseNumber(row[index]) if index < len(row) else None
+            if number is None:
+                column[1] += 1
+                continue
+            column[0] += 1
+            column[2] += number
+            column[3] = number if column[3] is None else min(column[3], number)
+            column[4] = number if column[4] is None else max(column[4], number)
+
+    def write(self, out, header):
+        for index, (count, skipped, total, low, high) in zip(self.indices, self.columns):
+            if count == 0:
+                out.write('aggregate %s: no numeric values, %d skipped\n' % (header[index], skipped))
+                continue
+            out.write('aggregate %s: count %d, skipped %d, sum %s, mean %s, min %s, max %s\n' % (header[index], count, skipped, formatNumber(total), formatNumber(total / count), formatNumber(low), formatNumber(high)))
+
+def observeColumns(rows, columnStats):
+    for row in rows:
+        columnStats.update(row)
+        yield row
+
+def bindExpression(node, header, base):
+    kind = node[0]
+    if kind == 'col':
+        return ('col', columnIndex(header, node[1], base))
+    if kind == 'neg':
+        return ('neg', bindExpression(node[1], header, base))
+    if kind == 'num':
+        return node
+    return (kind, bindExpression(node[1], header, base), bindExpression(node[2], header, base))
+
+def evaluate(node, row):
+    kind = node[0]
+    if kind == 'num':
+        return node[1]
+    if kind == 'col':
+        return parseNumber(row[node[1]]) if node[1] < len(row) else None
+    if kind == 'neg':
+        value = evaluate(node[1], row)
+        return None if value is None else -value
+    left = evaluate(node[1], row)
+    right = evaluate(node[2], row)
+    if left is None or right is None:
+        return None
+    if kind == '+':
+        return left + right
+    if kind == '-':
+        return left - right
+    if kind == '*':
+        return left * right
+    return left / right if right else None
+
+def addCoalesced(rows, indices):
+    for row in rows:
+        yield row + [next((row[index] for index in indices if index < len(row) and row[index]), '')]
+
+def addComputed(rows, node):
+    for row in rows:
+        value = evaluate(node, row)
+        yield row + ['' if value is None else formatNumber(value)]
+
+def markedLines(lines, start, end):
+    lines = iter(lines)
+    if start is not None:
+        for line in lines:
+            if line.strip() == start:
+                break
+        else:
+            die('start marker not found: %s' % start, EXIT_PARSE)
+    for line in lines:
+        if end is not None and line.strip() == end:
+            return
+        yield line
+    if end is not None:
+        sys.stderr.write('warning: end marker not found, read to end of input: %s\n' % end)
+
+def dumpCsv(infile, outfile, fields, stats, validator, args):
+    lines = infile
+    if args.start_marker is not None or args.end_marker is not None:
+        lines = markedLines(infile, args.start_marker, args.end_marker)
+    reject = None
+    if args.reject_file:
+        rejectfile = open(args.reject_file, 'w', newline='')
+        reject = csv.writer(rejectfile)
+        reject.writerow(['line', 'record', 'error'])
+    if args.detect_delimiter:
+        sample = list(itertools.islice(lines, 20))
+        args.delimiter = detectDelimiter([line for line in sample if args.comment_char is None or not line.startswith(args.comment_char)])
+        lines = itertools.chain(sample, lines)
+    reader = Records(lines, args, stats, validator, reject, getattr(infile, 'layout', None) or (locale.getpreferredencoding(False), 0))
+    rows = iter(reader)
+    header = next(rows, None)
+    if header is None:
+        if args.count:
+            outfile.write('%d\n' % stats.records)
+        return
+    if args.no_headers:
+        rows = itertools.chain([header], rows)
+        header = [str(index + args.index_base) for index in range(len(header))]
+    if args.header_rows > 1:
+        headers = [header] + [next(rows, []) for number in range(args.header_rows - 1)]
+        header = flattenHeaders(headers, args.header_joiner) if args.flatten_headers else headers[-1]
+    rows = untilInterrupted(rows)
+    if (reject is not None or args.validate) and args.on_field_count_mismatch_fill is None:
+        reader.width = len(header)
+    if args.rename_regex or args.trim_header_names:
+        header = renameHeader(header, args)
+    if args.validate_no_newlines:
+        if hasNewline(header):
+            validator.fail('header has a newline in a field')
+        rows = checkNewlines(rows, reader, validator)
+    if args.assert_monotonic:
+        index = columnIndex(header, args.assert_monotonic, args.index_base)
+        rows = checkMonotonic(rows, reader, index, args.monotonic_strict, validator)
+    if args.build_index:
+        column, path = args.build_index
+        index = columnIndex(header, column, args.index_base)
+        indexfile = open(path, 'w', newline='')
+        indexWriter = csv.writer(indexfile)
+        indexWriter.writerow(['key', 'offset'])
+        rows = indexRows(rows, reader, index, indexWriter)
+    if args.columns_dedup:
+        unique = uniqueColumns(header)
+        header = selectFields(header, unique)
+        rows = selectRows(rows, unique)
+    if args.on_field_count_mismatch_fill is not None:
+        rows = fillMissing(rows, len(header), args.on_field_count_mismatch_fill)
+    if args.schema_order_file:
+        order = readSchemaOrder(args.schema_order_file)
+        mapping = schemaMapping(header, order, args.drop_extra)
+        header = order + [header[index] for index in mapping[len(order):]]
+        rows = (reorderRow(row, mapping) for row in rows)
+    if args.replace or args.replace_regex:
+        targets = None
+        if args.replace_column:
+            targets = [columnIndex(header, column, args.index_base) for column in args.replace_column.split(',')]
+        rows = replaceValues(rows, args.replace or [], args.replace_regex or [], targets)
+    if args.sample_per_group:
+        column, size = args.sample_per_group
+        rows = samplePerGroup(rows, columnIndex(header, column, args.index_base), size, args.seed, args.max_memory)
+    if args.sample_head_tail:
+        rows = headTail(rows, args.sample_head_tail)
+    if args.running_total:
+        column, name = args.running_total
+        index = columnIndex(header, column, args.index_base)
+        group = None
+        if args.running_total_group:
+            group = columnIndex(header, args.running_total_group, args.index_base)
+        rows = addRunningTotal(rows, index, group)
+        header = header + [name]
+    for columns, name in args.coalesce or []:
+        sources = [columnIndex(header, column, args.index_base) for column in columns]
+        rows = addCoalesced(rows, sources)
+        header = header + [name]
+        if args.coalesce_drop:
+            keep = [index for index in range(len(header)) if index not in sources]
+            header = selectFields(header, keep)
+            rows = selectRows(rows, keep)
+    for name, node in args.compute or []:
+        rows = addComputed(rows, bindExpression(node, header, args.index_base))
+        header = header + [name]
+    if args.filter:
+        column, op, operand = args.filter
+        rows = filterRows(rows, columnIndex(header, column, args.index_base), op, operand)
+    if args.dedupe or args.dedupe_by:
+        keys = None
+        if args.dedupe_by:
+            keys = [columnIndex(header, column, args.index_base) for column in args.dedupe_by.split(',')]
+        rows = dedupeRows(rows, keys, stats, args.max_memory)
+    if args.sort_by:
+        rows = sortRows(rows, columnIndex(header, args.sort_by, args.index_base), args)
+    if args.group_by:
+        specs = [(function, None if column == '*' else columnIndex(header, column, args.index_base)) for function, column in args.agg]
+        rows = groupAggregates(rows, columnIndex(header, args.group_by, args.index_base), specs, args.group_strict)
+        header = [header[columnIndex(header, args.group_by, args.index_base)]] + ['count' if column == '*' else '%s_%s' % (function, column) for function, column in args.agg]
+    if args.skip or args.limit is not None:
+        rows = itertools.islice(rows, args.skip, None if args.limit is None else args.skip + args.limit)
+    if args.columns_only_nonempty:
+        header, rows = dropEmptyColumns(header, rows)
+    if args.select_type:
+        types, rows = inferTypes(header, rows, args.infer_rows)
+        keep = [index for index, (kind, null) in enumerate(types) if typeMatches(kind, args.select_type)]
+        header = selectFields(header, keep)
+        rows = selectRows(rows, keep)
+    if args.column_stats_out:
+        columnStats = ColumnStats(len(header))
+        rows = observeColumns(rows, columnStats)
+    if args.profile:
+        profile = ColumnProfile(len(header))
+        rows = observeColumns(rows, profile)
+    if args.aggregate:
+        aggregates = Aggregates([columnIndex(header, column, args.index_base) for column in args.aggregate.split(',')])
+        rows = observeColumns(rows, aggregates)
+    indices = selectedIndices(header, fields, args)
+    if args.add_column:
+        selected, indices = indices, None
+        rows = addConstants(selectRows(rows, selected), [value for name, value in args.add_column], lambda: getattr(infile, 'current', None) or args.infile or '-')
+        header = selectFields(header, selected) + [name for name, value in args.add_column]
+    if args.rename:
+        header = renameColumns(header, args.rename)
+    if args.json_schema_out:
+        types, rows = inferTypes(header, rows, args.infer_rows)
+        writeJsonSchema(args.json_schema_out, selectFields(header, indices), selectFields(types, indices))
+    if args.dry_validate:
+        for row in rows:
+            stats.count()
+    elif args.count:
+        for row in rows:
+            stats.count()
+        outfile.write('%d\n' % stats.records)
+    elif args.retention:
+        splitRetention(header, rows, indices, stats, args)
+    elif args.split_rows or args.split_bytes:
+        splitParts(header, rows, indices, stats, args)
+    elif args.split_by:
+        splitGroups(header, rows, indices, stats, args)
+    else:
+        writeRows(header, rows, indices, outfile, stats, args)
+    if args.build_index:
+        indexfile.close()
+    if reject is not None:
+        rejectfile.close()
+    if args.column_stats_out:
+        columnStats.write(args.column_stats_out, header)
+    if args.profile and args.stats_format == 'json':
+        stats.profile = (header, profile)
+    elif args.profile:
+        profile.write(sys.stderr, header)
+    if args.aggregate:
+        aggregates.write(sys.stderr, header)
 
 if __name__ == "__main__":
     args = ArgParse()
@@ -42,6 +2343,95 @@ if __name__ == "__main__":
         fields = 0
     else:
         fields = args.fields.split(',')
-    dumpCsv(args.infile, fields)
-
-
+    infile, counter = openInput(args.infiles, args)
+    if args.pick:
+        if args.infile is None or not infile.seekable() or not sys.stdin.isatty() or not sys.stdout.isatty():
+            die('--pick requires a seekable FILE argument and an interactive terminal', EXIT_CONFIG)
+        header = next(csv.reader(infile, **readerOptions(args)), [])
+        infile.seek(0)
+        try:
+            import curses
+        except ImportError:
+            die('--pick needs the curses module, which this Python does not provide', EXIT_CONFIG)
+        fields = resolvePick(curses.wrapper(pickFields, header), args.index_base)
+        if fields is None:
+            die('no fields selected', EXIT_CONFIG)
+    total = None
+    if args.prescan:
+        if not infile.seekable():
+            die('--prescan requires a seekable input', EXIT_CONFIG)
+        total = countRecords(infile, args)
+        if not args.no_headers:
+            total = max(total - args.header_rows, 0)
+        infile.seek(0)
+    stats = Stats(total, Histogram() if args.timing_histogram else None, counter, args.rate_window)
+    signal.signal(signal.SIGINT, interrupt)
+    done = threading.Event()
+    reporters = []
+    if args.stats_interval:
+        reporters.append(threading.Thread(target=reportStats, args=(stats, args.stats_interval, done)))
+    if args.progress and sys.stderr.isatty():
+        reporters.append(threading.Thread(target=reportProgress, args=(stats, inputSize(args.infiles), done)))
+    for reporter in reporters:
+        reporter.daemon = True
+        reporter.start()
+    validator = Validator(args.dry_validate and not args.strict, 1 if args.dry_validate else EXIT_PARSE)
+    outfile, tmp = openOutput(None if args.split_rows or args.split_bytes or args.split_by else args.output, args)
+    missing = 0
+    try:
+        try:
+            if args.lookup:
+                missing = lookupRecords(outfile, fields, args)
+            else:
+                dumpCsv(infile, outfile, fields, stats, validator, args)
+        except KeyboardInterrupt:
+            # A second Ctrl-C, e.g. while the input is stalled: keep what was written.
+            interrupted.set()
+        outfile.flush()
+        if outfile is not sys.stdout:
+            outfile.close()
+        if tmp:
+            os.replace(tmp, args.output)
+            if args.bgzip_output:
+                os.replace(tmp + '.gzi', args.output + '.gzi')
+            tmp = None
+    except UnicodeDecodeError as e:
+        die('input is not valid %s (%s); see --encoding' % (e.encoding, e.reason), EXIT_PARSE)
+    except BrokenPipeError:
+        os.dup2(os.open(os.devnull, os.O_WRONLY), sys.stdout.fileno())
+        sys.exit(EXIT_IO)
+    except http.client.HTTPException as e:
+        die('input download failed: %r' % e, EXIT_IO)
+    except OSError as e:
+        if e.errno == errno.ENOSPC:
+            die('no space left on device after processing %d records; the output is incomplete' % stats.records, EXIT_IO)
+        die(str(e), EXIT_IO)
+    finally:
+        done.set()
+        for reporter in reporters:
+            reporter.join()
+        if tmp:
+            try:
+                outfile.close()
+            except OSError:
+                pass
+            os.remove(tmp)
+            if os.path.exists(tmp + '.gzi'):
+                os.remove(tmp + '.gzi')
+    if args.stats_format == 'json':
+        if args.stats_file:
+            writeStatsFile(args.stats_file, json.dumps(stats.summary(), indent=2) + '\n')
+        else:
+            sys.stderr.write(json.dumps(stats.summary()) + '\n')
+    elif args.stats_file:
+        writeStatsFile(args.stats_file, 'stats: %s\n' % stats.snapshot())
+    elif args.stats:
+        sys.stderr.write('stats: %s\n' % stats.snapshot())
+    if args.timing_histogram:
+        sys.stderr.write('timing: %s\n' % stats.histogram.summary())
+    if interrupted.is_set():
+        die('interrupted: %s' % stats.snapshot(), 130)
+    if validator.problems:
+        die('validation failed with %d problems:\n  %s' % (len(validator.problems), '\n  '.join(validator.problems)), validator.status)
+    if missing and args.fail_missing:
+        sys.exit(1)
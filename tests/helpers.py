@@ -0,0 +1,55 @@
+import importlib.util
+import os
+import subprocess
+import sys
+import tempfile
+import time
+import unittest
+
+SCRIPT = os.path.join(os.path.dirname(os.path.dirname(os.path.abspath(__file__))), 'csvparser.py')
+
+
+def load_module():
+    spec = importlib.util.spec_from_file_location('csvparser', SCRIPT)
+    module = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(module)
+    return module
+
+
+class CsvParserTest(unittest.TestCase):
+    """Runs csvparser.py as a subprocess against files in a scratch directory."""
+
+    def setUp(self):
+        scratch = tempfile.TemporaryDirectory()
+        self.addCleanup(scratch.cleanup)
+        self.dir = scratch.name
+
+    def path(self, name):
+        return os.path.join(self.dir, name)
+
+    def write(self, name, content):
+        mode = 'wb' if isinstance(content, bytes) else 'w'
+        with open(self.path(name), mode, **({} if isinstance(content, bytes) else {'newline': ''})) as f:
+            f.write(content)
+        return self.path(name)
+
+    def read(self, name, mode='r'):
+        # Like the captured stdout, text reads turn \r\n into \n.
+        with open(self.path(name), mode) as f:
+            return f.read()
+
+    def run_parser(self, *args, input=None, status=0, **kwargs):
+        text = not isinstance(input, bytes)
+        result = subprocess.run([sys.executable, SCRIPT] + list(args), input=input, capture_output=True, text=text, cwd=self.dir, **kwargs)
+        if status is not None:
+            self.assertEqual(result.returncode, status, result.stderr)
+        return result
+
+    def start_parser(self, *args, **kwargs):
+        return subprocess.Popen([sys.executable, SCRIPT] + list(args), stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True, cwd=self.dir, **kwargs)
+
+    def feed_slowly(self, process, lines, delay):
+        for line in lines:
+            process.stdin.write(line)
+            process.stdin.flush()
+            time.sleep(delay)
@@ -0,0 +1,82 @@
+from tests.helpers import CsvParserTest
+
+
+class ColumnsDedupTest(CsvParserTest):
+    def test_repeated_header_column_removed(self):
+        self.write('in.csv', 'a,b,a,c\n1,2,3,4\n')
+        result = self.run_parser('--columns-dedup', 'in.csv')
+        self.assertEqual(result.stdout, 'a,b,c\n1,2,4\n')
+
+
+class RunningTotalTest(CsvParserTest):
+    def test_cumulative_values(self):
+        self.write('in.csv', 'g,n\nx,1\nx,2.5\ny,x\ny,4\n')
+        result = self.run_parser('--running-total', 'n:total', 'in.csv')
+        self.assertEqual(result.stdout, 'g,n,total\nx,1,1\nx,2.5,3.5\ny,x,3.5\ny,4,7.5\n')
+
+    def test_restart_per_group(self):
+        self.write('in.csv', 'g,n\nx,1\nx,2\ny,3\ny,4\n')
+        result = self.run_parser('--running-total', 'n:total', '--running-total-group', 'g', 'in.csv')
+        self.assertEqual(result.stdout, 'g,n,total\nx,1,1\nx,2,3\ny,3,3\ny,4,7\n')
+
+
+class FlattenHeadersTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'id,price,,volume\n,open,close,\n1,2,3,4\n')
+
+    def test_two_header_rows_are_combined(self):
+        result = self.run_parser('--header-rows', '2', '--flatten-headers', 'in.csv')
+        self.assertEqual(result.stdout, 'id,price.open,price.close,volume\n1,2,3,4\n')
+
+    def test_custom_joiner_and_selection(self):
+        result = self.run_parser('--header-rows', '2', '--flatten-headers', '--header-joiner', '_', '-c', 'price_close', 'in.csv')
+        self.assertEqual(result.stdout, 'price_close\n3\n')
+
+
+class OnlyNonemptyTest(CsvParserTest):
+    def test_all_empty_column_is_dropped(self):
+        self.write('in.csv', 'a,empty,partial,b\n1,,,x\n2,,y,\n3,,,z\n')
+        result = self.run_parser('--columns-only-nonempty', 'in.csv')
+        self.assertEqual(result.stdout, 'a,partial,b\n1,,x\n2,y,\n3,,z\n')
+
+    def test_from_stdin(self):
+        result = self.run_parser('--columns-only-nonempty', '-f', '2', input='a,empty,b\n1,,x\n2,,y\n')
+        self.assertEqual(result.stdout, 'b\nx\ny\n')
+
+
+class ComputeTest(CsvParserTest):
+    def test_product_column(self):
+        self.write('in.csv', 'price,quantity\n2.5,4\n3,7\nn/a,1\n')
+        result = self.run_parser('--compute', 'total = price * quantity', 'in.csv')
+        self.assertEqual(result.stdout, 'price,quantity,total\n2.5,4,10\n3,7,21\nn/a,1,\n')
+
+    def test_precedence_and_parentheses(self):
+        self.write('in.csv', 'a,b\n1,2\n')
+        result = self.run_parser('--compute', 'x = a + b * 3', '--compute', 'y = (a + b) * 3', '-c', 'x,y', 'in.csv')
+        self.assertEqual(result.stdout, 'x,y\n7,9\n')
+
+
+class MinColumnsTest(CsvParserTest):
+    def test_three_columns_padded_to_five(self):
+        self.write('in.csv', 'a,b,c\n1,2,3\n4,5,6\n')
+        result = self.run_parser('--min-columns', '5', 'in.csv')
+        self.assertEqual(result.stdout, 'a,b,c,,\n1,2,3,,\n4,5,6,,\n')
+
+    def test_wider_rows_are_untouched(self):
+        self.write('in.csv', 'a,b,c\n1,2,3\n')
+        self.assertEqual(self.run_parser('--min-columns', '2', 'in.csv').stdout, 'a,b,c\n1,2,3\n')
+
+
+class CoalesceTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'id,a,b\n1,primary,secondary\n2,,fallback\n3,,\n')
+
+    def test_first_non_empty_value(self):
+        result = self.run_parser('--coalesce', 'a,b:email', 'in.csv')
+        self.assertEqual(result.stdout, 'id,a,b,email\n1,primary,secondary,primary\n2,,fallback,fallback\n3,,,\n')
+
+    def test_sources_dropped(self):
+        result = self.run_parser('--coalesce', 'a,b:email', '--coalesce-drop', 'in.csv')
+        self.assertEqual(result.stdout, 'id,email\n1,primary\n2,fallback\n3,\n')
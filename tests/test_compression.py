@@ -0,0 +1,29 @@
+import gzip
+
+from tests.helpers import CsvParserTest
+
+
+class GzipInputTest(CsvParserTest):
+    def test_record_split_across_members(self):
+        # Concatenated members, as produced by appending gzip files; the quoted
+        # field of row 2 starts in the first member and ends in the second.
+        self.write('in.csv.gz', gzip.compress(b'id,note\n1,a\n2,"multi\n') + gzip.compress(b'line"\n3,c\n'))
+        result = self.run_parser('in.csv.gz')
+        self.assertEqual(result.stdout, 'id,note\n1,a\n2,"multi\nline"\n3,c\n')
+
+    def test_piped_stdin_with_flag(self):
+        result = self.run_parser('--input-compression', 'gzip', '-c', 'note', input=gzip.compress(b'id,note\n1,a\n2,b\n'))
+        self.assertEqual(result.stdout, b'note\r\na\r\nb\r\n')
+
+
+class GzipOutputTest(CsvParserTest):
+    def test_round_trip(self):
+        text = 'id,name\n' + ''.join('%d,"name, %d"\n' % (number, number) for number in range(5000))
+        self.write('in.csv', text)
+        self.run_parser('--output-compression', 'gzip', '-o', 'out.csv.gz', 'in.csv')
+        self.assertEqual(gzip.decompress(self.read('out.csv.gz', 'rb')).decode().replace('\r\n', '\n'), text)
+
+    def test_round_trip_through_stdout(self):
+        self.write('in.csv', 'a,b\n1,2\n')
+        result = self.run_parser('--output-compression', 'gzip', 'in.csv', input=b'')
+        self.assertEqual(gzip.decompress(result.stdout), b'a,b\r\n1,2\r\n')
@@ -0,0 +1,27 @@
+from tests.helpers import CsvParserTest
+
+
+class ConfigTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a,b,c\n1,2,3\n')
+
+    def test_integer_choice_from_file(self):
+        self.write('config.toml', 'index_base = 0\nfields = "1"\n')
+        result = self.run_parser('--config', 'config.toml', 'in.csv')
+        self.assertEqual(result.stdout, 'b\n2\n')
+
+    def test_invalid_choice_from_file(self):
+        self.write('config.toml', 'index_base = 3\n')
+        result = self.run_parser('--config', 'config.toml', 'in.csv', status=2)
+        self.assertIn('config key index_base: invalid choice 3', result.stderr)
+
+    def test_command_line_overrides_alternative_from_file(self):
+        self.write('config.toml', 'fields = "1"\n')
+        result = self.run_parser('--config', 'config.toml', '-c', 'c', 'in.csv')
+        self.assertEqual(result.stdout, 'c\n3\n')
+
+    def test_command_line_overrides_same_option(self):
+        self.write('config.toml', 'fields = "1"\n')
+        result = self.run_parser('--config', 'config.toml', '-f', '2', 'in.csv')
+        self.assertEqual(result.stdout, 'b\n2\n')
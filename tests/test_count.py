@@ -0,0 +1,23 @@
+from tests.helpers import CsvParserTest
+
+
+class CountTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a\n' + ''.join('%d\n' % number for number in range(10)))
+
+    def test_counts_data_rows(self):
+        self.assertEqual(self.run_parser('--count', 'in.csv').stdout, '10\n')
+
+    def test_no_headers_counts_first_row(self):
+        self.assertEqual(self.run_parser('--count', '--no-headers', 'in.csv').stdout, '11\n')
+
+    def test_skip_and_limit(self):
+        self.assertEqual(self.run_parser('--count', '--skip', '3', '--limit', '5', 'in.csv').stdout, '5\n')
+
+    def test_empty_input(self):
+        self.write('empty.csv', '')
+        self.assertEqual(self.run_parser('--count', 'empty.csv').stdout, '0\n')
+        self.assertEqual(self.run_parser('--count', '--no-headers', 'empty.csv').stdout, '0\n')
+        self.write('header.csv', 'a\n')
+        self.assertEqual(self.run_parser('--count', 'header.csv').stdout, '0\n')
@@ -0,0 +1,48 @@
+import base64
+import codecs
+
+from tests.helpers import CsvParserTest
+
+
+class BomTest(CsvParserTest):
+    def test_utf8_bom_does_not_stick_to_first_header(self):
+        self.write('in.csv', codecs.BOM_UTF8 + b'id,name\n1,a\n')
+        result = self.run_parser('-c', 'id', 'in.csv')
+        self.assertEqual(result.stdout, 'id\n1\n')
+
+    def test_utf16_bom_error_position(self):
+        text = 'id,name\n1,a\n2\n'
+        self.write('in.csv', codecs.BOM_UTF16_LE + text.encode('utf-16-le'))
+        result = self.run_parser('--validate', 'in.csv', status=4)
+        offset = 2 + len('id,name\n1,a\n'.encode('utf-16-le'))
+        self.assertIn('(line 3, byte %d)' % offset, result.stderr)
+
+    def test_utf8_bom_error_position(self):
+        self.write('in.csv', codecs.BOM_UTF8 + b'id,name\n1,a\n2\n')
+        result = self.run_parser('--validate', 'in.csv', status=4)
+        self.assertIn('(line 3, byte 15)', result.stderr)
+
+
+class Base64InputTest(CsvParserTest):
+    def test_wrapped_base64_stdin_is_decoded(self):
+        text = 'id,name\n' + ''.join('%d,name %d\n' % (number, number) for number in range(200))
+        encoded = base64.encodebytes(text.encode())
+        result = self.run_parser('--base64-input', '-c', 'name', input=encoded)
+        self.assertEqual(result.stdout.decode().replace('\r\n', '\n'), 'name\n' + ''.join('name %d\n' % number for number in range(200)))
+
+    def test_invalid_base64_fails(self):
+        result = self.run_parser('--base64-input', input=b'aWQ*bmFtZQo=\n', status=4)
+        self.assertIn(b'invalid base64 input', result.stderr)
+
+
+class TranscodingTest(CsvParserTest):
+    def test_utf16le_with_bom_is_detected(self):
+        self.write('in.csv', codecs.BOM_UTF16_LE + 'id,name\n1,café\n2,naïve\n'.encode('utf-16-le'))
+        for args in [(), ('--encoding', 'utf16le')]:
+            result = self.run_parser(*args + ('-c', 'name', 'in.csv'), input=b'')
+            self.assertEqual(result.stdout, 'name\r\ncafé\r\nnaïve\r\n'.encode('utf-8'))
+
+    def test_latin1_accents(self):
+        self.write('in.csv', 'id,name\n1,café\n2,Müller\n'.encode('latin-1'))
+        result = self.run_parser('--encoding', 'latin1', 'in.csv', input=b'')
+        self.assertEqual(result.stdout, 'id,name\r\n1,café\r\n2,Müller\r\n'.encode('utf-8'))
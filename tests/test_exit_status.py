@@ -0,0 +1,24 @@
+import io
+from unittest import mock
+
+from tests.helpers import CsvParserTest, load_module
+
+
+class ExitStatusTest(CsvParserTest):
+    def test_unsafe_split_by_value_is_an_io_error(self):
+        self.write('in.csv', 'k,v\n..,1\n')
+        result = self.run_parser('--split-by', 'k', '-o', 'out.csv', 'in.csv', status=3)
+        self.assertIn('unsafe --split-by value', result.stderr)
+
+    def test_unwritable_stats_file_fails_before_reading(self):
+        self.write('in.csv', 'a\n1\n')
+        result = self.run_parser('--stats-file', 'missing/stats.txt', 'in.csv', status=3)
+        self.assertIn("can't write 'missing/stats.txt': No such file or directory", result.stderr)
+        self.assertEqual(result.stdout, '')
+
+    def test_stats_file_write_error_is_an_io_error(self):
+        module = load_module()
+        with mock.patch('sys.stderr', io.StringIO()) as stderr, self.assertRaises(SystemExit) as raised:
+            module.writeStatsFile(self.path('missing/stats.txt'), 'stats\n')
+        self.assertEqual(raised.exception.code, 3)
+        self.assertIn('No such file or directory', stderr.getvalue())
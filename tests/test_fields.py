@@ -0,0 +1,60 @@
+from tests.helpers import CsvParserTest
+
+
+class FieldListTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a,b,c\n1,2,3\n')
+
+    def test_ranges_and_open_ends(self):
+        self.assertEqual(self.run_parser('-f', '2-', 'in.csv').stdout, 'b,c\n2,3\n')
+        self.assertEqual(self.run_parser('-f', '3,1-2', 'in.csv').stdout, 'c,a,b\n3,1,2\n')
+
+    def test_index_beyond_header(self):
+        result = self.run_parser('-f', '5', 'in.csv', status=2)
+        self.assertIn('field 5 is beyond the 3 columns of the header', result.stderr)
+
+    def test_range_start_beyond_header(self):
+        result = self.run_parser('-f', '1,8-', 'in.csv', status=2)
+        self.assertIn('field 8- is beyond the 3 columns of the header', result.stderr)
+
+    def test_no_headers_is_not_checked(self):
+        result = self.run_parser('--no-headers', '-f', '1,4', 'in.csv')
+        self.assertEqual(result.stdout, 'a\n1\n')
+
+
+class SelectionOrderTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a,b,c\n1,2,3\n4,5,6\n')
+
+    def test_indices_in_requested_order(self):
+        self.assertEqual(self.run_parser('-f', '3,1,2', 'in.csv').stdout, 'c,a,b\n3,1,2\n6,4,5\n')
+
+    def test_names_in_requested_order(self):
+        self.assertEqual(self.run_parser('-c', 'b,c,a', 'in.csv').stdout, 'b,c,a\n2,3,1\n5,6,4\n')
+
+
+class ProjectionTest(CsvParserTest):
+    def test_matches_naive_projection(self):
+        header = ['c%d' % index for index in range(30)]
+        rows = [['r%dc%d' % (number, index) for index in range(30)] for number in range(50)]
+        self.write('in.csv', '\n'.join(','.join(row) for row in [header] + rows) + '\n')
+        picked = [29, 0, 14, 14, 3]
+        result = self.run_parser('-f', ','.join(str(index + 1) for index in picked), 'in.csv')
+        expected = ''.join(','.join(row[index] for index in picked) + '\n' for row in [header] + rows)
+        self.assertEqual(result.stdout, expected)
+
+
+class IndexBaseTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a,b,c\n1,2,3\n')
+
+    def test_zero_is_an_error_in_one_based_mode(self):
+        result = self.run_parser('--fields', '0', 'in.csv', status=2)
+        self.assertIn('0 is invalid', result.stderr)
+
+    def test_zero_based_alias(self):
+        result = self.run_parser('--columns-index-base', '0', '--fields', '0,2', 'in.csv')
+        self.assertEqual(result.stdout, 'a,c\n1,3\n')
@@ -0,0 +1,178 @@
+import csv
+import io
+import json
+import struct
+import zipfile
+
+from tests.helpers import CsvParserTest
+
+
+class FieldSeparatorTest(CsvParserTest):
+    def test_concatenate_without_separator(self):
+        self.write('in.csv', 'a,b,c\n1,2,3\nx,y,z\n')
+        result = self.run_parser('-f', '1,3', '--field-separator-in-output', 'NONE', 'in.csv')
+        self.assertEqual(result.stdout, 'ac\n13\nxz\n')
+
+
+class NullDelimitedTest(CsvParserTest):
+    def test_one_column_byte_layout(self):
+        self.write('in.csv', 'name,n\na b,1\n"x\ny",2\n,3\n')
+        result = self.run_parser('-c', 'name', '--format', 'null-delimited', 'in.csv', input=b'')
+        self.assertEqual(result.stdout, b'a b\0x\ny\0\0')
+
+    def test_needs_one_field(self):
+        self.write('in.csv', 'a,b\n1,2\n')
+        result = self.run_parser('--format', 'null-delimited', 'in.csv', status=2)
+        self.assertIn('needs exactly one field', result.stderr)
+
+
+def read_avro(data):
+    """Decodes an uncompressed Avro container of a flat record of nullable primitives."""
+    position = 0
+
+    def long():
+        nonlocal position
+        shift = value = 0
+        while True:
+            byte = data[position]
+            position += 1
+            value |= (byte & 0x7f) << shift
+            shift += 7
+            if not byte & 0x80:
+                return (value >> 1) ^ -(value & 1)
+
+    def raw(size):
+        nonlocal position
+        position += size
+        return data[position - size:position]
+
+    def value(kind):
+        if kind == 'long':
+            return long()
+        if kind == 'double':
+            return struct.unpack('<d', raw(8))[0]
+        if kind == 'boolean':
+            return raw(1) == b'\x01'
+        return raw(long()).decode('utf-8')
+
+    assert raw(4) == b'Obj\x01'
+    meta = {}
+    count = long()
+    while count:
+        for number in range(count):
+            key = raw(long()).decode()
+            meta[key] = raw(long())
+        count = long()
+    assert meta['avro.codec'] == b'null'
+    schema = json.loads(meta['avro.schema'])
+    sync = raw(16)
+    records = []
+    while position < len(data):
+        count = long()
+        end = long() + position
+        for number in range(count):
+            record = {}
+            for field in schema['fields']:
+                branch = long()
+                record[field['name']] = None if branch == 0 else value(field['type'][branch])
+            records.append(record)
+        assert position == end
+        assert raw(16) == sync
+    return schema, records
+
+
+class AvroOutputTest(CsvParserTest):
+    def test_round_trip(self):
+        self.write('in.csv', 'id,price,ok,name\n1,2.5,true,café\n2,,false,\n')
+        result = self.run_parser('--output-format', 'avro', 'in.csv', input=b'')
+        schema, records = read_avro(result.stdout)
+        self.assertEqual([(field['name'], field['type']) for field in schema['fields']], [
+            ('id', ['null', 'long']), ('price', ['null', 'double']), ('ok', ['null', 'boolean']), ('name', ['null', 'string'])])
+        self.assertEqual(records, [
+            {'id': 1, 'price': 2.5, 'ok': True, 'name': 'café'},
+            {'id': 2, 'price': None, 'ok': False, 'name': None}])
+
+
+class ZipInputTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        with zipfile.ZipFile(self.path('bundle.zip'), 'w', zipfile.ZIP_DEFLATED) as archive:
+            archive.writestr('readme.txt', 'not csv\n')
+            archive.writestr('orders.csv', 'id,total\n1,10\n2,20\n')
+            archive.writestr('customers.csv', 'id,name\n7,a\n8,b\n')
+
+    def test_named_member(self):
+        result = self.run_parser('--zip-member', 'customers.csv', 'bundle.zip')
+        self.assertEqual(result.stdout, 'id,name\n7,a\n8,b\n')
+
+    def test_named_member_from_stdin(self):
+        result = self.run_parser('--zip-member', 'orders.csv', '-c', 'total', input=self.read('bundle.zip', 'rb'))
+        self.assertEqual(result.stdout.replace(b'\r\n', b'\n'), b'total\n10\n20\n')
+
+    def test_missing_member(self):
+        result = self.run_parser('--zip-member', 'nope.csv', 'bundle.zip', status=2)
+        self.assertIn('no such zip member: nope.csv', result.stderr)
+
+
+class TrimTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', ' id , name \n 1 ,"  padded"\n2,  plain  \n')
+
+    def test_all(self):
+        result = self.run_parser('--trim', 'all', '-c', 'name,id', 'in.csv')
+        self.assertEqual(result.stdout, 'name,id\n  padded,1\nplain,2\n')
+
+    def test_headers_only(self):
+        result = self.run_parser('--trim', 'headers', '-c', 'name', 'in.csv')
+        self.assertEqual(result.stdout, 'name\n  padded\n  plain  \n')
+
+    def test_fields_only(self):
+        result = self.run_parser('--trim', 'fields', 'in.csv')
+        self.assertEqual(result.stdout, ' id , name \n1,  padded\n2,plain\n')
+
+
+class DetectDelimiterTest(CsvParserTest):
+    def test_each_candidate(self):
+        expected = {',': 'note,id\n"x, y; z",1\n', '\t': 'note\tid\nx, y; z\t1\n', ';': 'note;id\n"x, y; z";1\n', '|': 'note|id\nx, y; z|1\n'}
+        for delimiter, output in expected.items():
+            with self.subTest(delimiter=delimiter):
+                self.write('in.csv', 'id{0}name{0}note\n1{0}a{0}"x, y; z"\n'.format(delimiter))
+                self.assertEqual(self.run_parser('--detect-delimiter', '-c', 'note,id', 'in.csv').stdout, output)
+
+    def test_ambiguous(self):
+        self.write('in.csv', 'a,b;c\n1,2;3\n')
+        result = self.run_parser('--detect-delimiter', 'in.csv', status=2)
+        self.assertIn("cannot detect the delimiter (candidates: ',' ';'); give it with -d", result.stderr)
+
+
+class QuoteEscapeTest(CsvParserTest):
+    def round_trip(self, content, *args, **dialect):
+        self.write('in.csv', content)
+        output = self.run_parser(*args, 'in.csv').stdout
+        again = self.run_parser(*args, input=output).stdout
+        self.assertEqual(again, output)
+        return list(csv.reader(io.StringIO(output), **dialect))
+
+    def test_single_quoted(self):
+        rows = self.round_trip("id,name\n1,'a, b'\n2,'it''s'\n", '--quote', "'", quotechar="'")
+        self.assertEqual(rows, [['id', 'name'], ['1', 'a, b'], ['2', "it's"]])
+
+    def test_backslash_escaped(self):
+        rows = self.round_trip('id,name\n1,a\\,b\n2,say \\"hi\\"\n', '--escape', '\\', escapechar='\\', doublequote=False)
+        self.assertEqual(rows, [['id', 'name'], ['1', 'a,b'], ['2', 'say "hi"']])
+
+
+class JsonLinesTest(CsvParserTest):
+    def test_objects_keyed_by_header(self):
+        result = self.run_parser('--format', 'jsonl', input='a,b\n1,café\n')
+        self.assertEqual([json.loads(line) for line in result.stdout.splitlines()], [{'a': '1', 'b': 'café'}])
+
+    def test_repeated_header_names_are_rejected(self):
+        result = self.run_parser('--format', 'jsonl', input='a,a,b\n1,2,3\n', status=2)
+        self.assertIn('duplicate column names in jsonl output: a; see --columns-dedup or --rename', result.stderr)
+        self.assertEqual(result.stdout, '')
+
+    def test_arrays_without_headers(self):
+        result = self.run_parser('--format', 'jsonl', '--no-headers', input='a,a\n1,2\n')
+        self.assertEqual(result.stdout, '["a", "a"]\n["1", "2"]\n')
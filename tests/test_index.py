@@ -0,0 +1,46 @@
+import codecs
+import csv
+import io
+
+from tests.helpers import CsvParserTest
+
+
+class BuildIndexTest(CsvParserTest):
+    def offsets(self, name):
+        rows = list(csv.reader(io.StringIO(self.read(name))))
+        self.assertEqual(rows[0], ['key', 'offset'])
+        return [(key, int(offset)) for key, offset in rows[1:]]
+
+    def test_offsets_slice_to_records(self):
+        data = '#note\nk,v\n#skip\n1,a\n"2\nz",b\n3,c\n'
+        self.write('in.csv', data)
+        self.run_parser('--comment-char', '#', '--build-index', 'k:in.idx', 'in.csv')
+        encoded = data.encode('utf-8')
+        records = [next(csv.reader(io.StringIO(encoded[offset:].decode('utf-8')))) for key, offset in self.offsets('in.idx')]
+        self.assertEqual(records, [['1', 'a'], ['2\nz', 'b'], ['3', 'c']])
+        self.assertEqual([key for key, offset in self.offsets('in.idx')], ['1', '2\nz', '3'])
+
+    def test_offsets_count_the_bom(self):
+        self.write('in.csv', codecs.BOM_UTF8 + b'k,v\n1,a\n2,b\n')
+        self.run_parser('--build-index', 'k:in.idx', 'in.csv')
+        self.assertEqual(self.offsets('in.idx'), [('1', 7), ('2', 11)])
+
+
+class LookupTest(CsvParserTest):
+    def test_lookup_returns_the_indexed_record(self):
+        self.write('in.csv', '#c\nk,v\n#x\n1,a\n2,b\n3,c\n')
+        self.run_parser('--comment-char', '#', '--build-index', 'k:in.idx', 'in.csv')
+        result = self.run_parser('--comment-char', '#', '--lookup', 'in.idx', '--key', '2', 'in.csv')
+        self.assertEqual(result.stdout, 'k,v\n2,b\n')
+
+    def test_lookup_with_dialect_and_selection(self):
+        self.write('in.csv', "k;v\n1;'x;y'\n")
+        self.run_parser('-d', ';', '--quote', "'", '--build-index', 'k:in.idx', 'in.csv')
+        result = self.run_parser('-d', ';', '--quote', "'", '--lookup', 'in.idx', '--key', '1', '-c', 'v', 'in.csv')
+        self.assertEqual(result.stdout, "v\n'x;y'\n")
+
+    def test_missing_key(self):
+        self.write('in.csv', 'k,v\n1,a\n')
+        self.run_parser('--build-index', 'k:in.idx', 'in.csv')
+        result = self.run_parser('--lookup', 'in.idx', '--key', '9', '--fail-missing', 'in.csv', status=1)
+        self.assertIn('key not found: 9', result.stderr)
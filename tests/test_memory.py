@@ -0,0 +1,46 @@
+import sys
+import unittest
+from unittest import mock
+
+from tests.helpers import CsvParserTest, load_module
+
+
+class MaxMemoryTest(CsvParserTest):
+    def test_in_memory_operation_rejected_under_tiny_cap(self):
+        self.write('in.csv', 'k,v\n1,a\n2,b\n')
+        result = self.run_parser('--max-memory', '1', '--sample-per-group', 'k:1', 'in.csv', status=2)
+        self.assertIn('exceeded --max-memory 1MB', result.stderr)
+
+    def test_sort_run_rejected_under_tiny_cap(self):
+        self.write('in.csv', 'k\n2\n1\n')
+        result = self.run_parser('--max-memory', '1', '--sort-by', 'k', 'in.csv', status=2)
+        self.assertIn('--sort-by exceeded --max-memory 1MB', result.stderr)
+
+
+class WithoutResourceTest(unittest.TestCase):
+    def test_loads_and_measures_without_resource(self):
+        with mock.patch.dict(sys.modules, {'resource': None}):
+            csvparser = load_module()
+            peak = csvparser.peakMemory()
+        self.assertEqual(peak, csvparser.currentMemory())
+
+
+class AutoBufferTest(unittest.TestCase):
+    def test_buffer_scales_with_file_size_within_bounds(self):
+        bufferSize = load_module().bufferSize
+        sizes = [bufferSize(filesize, None) for filesize in (0, 10 ** 6, 2 ** 26, 2 ** 30, 2 ** 40)]
+        self.assertEqual(sizes, [64 * 1024, 64 * 1024, 64 * 1024, 1024 * 1024, 4 * 1024 * 1024])
+        self.assertEqual(sizes, sorted(sizes))
+
+    def test_memory_cap_shrinks_the_buffer(self):
+        bufferSize = load_module().bufferSize
+        self.assertEqual(bufferSize(2 ** 40, 16 * 1024 * 1024), 256 * 1024)
+        self.assertEqual(bufferSize(2 ** 40, 1024), 4096)
+
+
+class AutoBufferRunTest(CsvParserTest):
+    def test_reported_on_stderr(self):
+        self.write('in.csv', 'a\n1\n')
+        result = self.run_parser('--auto-buffer', 'in.csv')
+        self.assertIn('read buffer: 65536 bytes', result.stderr)
+        self.assertEqual(result.stdout, 'a\n1\n')
@@ -0,0 +1,51 @@
+from tests.helpers import CsvParserTest
+
+
+class EarlyCheckTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'a,b,c\n1,2,3\n')
+
+    def assertRejected(self, message, *args):
+        result = self.run_parser(*args, status=2)
+        self.assertIn(message, result.stderr)
+        self.assertEqual(result.stdout, '')
+
+    def test_invalid_field(self):
+        self.assertRejected('invalid field: x', '-f', '1,x', 'in.csv')
+
+    def test_zero_field_in_one_based_mode(self):
+        self.assertRejected('field indices are 1-based; 0 is invalid', '-f', '0', 'in.csv')
+
+    def test_reversed_range(self):
+        self.assertRejected('invalid field range: 3-2', '-f', '3-2', 'in.csv')
+
+    def test_exclusive_selections(self):
+        self.assertRejected('--fields, --columns and --exclude cannot be used together', '-f', '1', '-c', 'a', 'in.csv')
+
+    def test_non_positive_count(self):
+        self.assertRejected('expected a positive integer', '--split-rows', '0', '-o', 'out.csv', 'in.csv')
+
+    def test_output_is_the_input(self):
+        self.assertRejected('is both the input and an output', '-o', './in.csv', 'in.csv')
+        self.assertEqual(self.read('in.csv'), 'a,b,c\n1,2,3\n')
+
+    def test_reject_file_is_the_input(self):
+        self.assertRejected('is both the input and an output', '--reject-file', 'in.csv', 'in.csv')
+
+    def test_retention_with_output(self):
+        self.assertRejected('--output is not used', '--retention', 'a:2020-01-01', '--old-out', 'old.csv', '--current-out', 'new.csv', '-o', 'out.csv', 'in.csv')
+
+
+class StdinDashTest(CsvParserTest):
+    def test_dash_reads_stdin(self):
+        self.assertEqual(self.run_parser('-', input='a,b\n1,2\n').stdout, 'a,b\n1,2\n')
+
+    def test_dash_among_several_files(self):
+        self.write('in.csv', 'a,b\n1,2\n')
+        result = self.run_parser('in.csv', '-', '--add-column', 'from={filename}', input='a,b\n3,4\n')
+        self.assertEqual(result.stdout, 'a,b,from\n1,2,in.csv\n3,4,-\n')
+
+    def test_lookup_needs_a_real_file(self):
+        result = self.run_parser('--lookup', 'index.csv', '--key', 'x', '-', input='a\n', status=2)
+        self.assertIn('--lookup requires a FILE argument', result.stderr)
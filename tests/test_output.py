@@ -0,0 +1,53 @@
+import gzip
+import os
+import struct
+import subprocess
+import sys
+import unittest
+
+from tests.helpers import CsvParserTest, SCRIPT
+
+
+class DiskFullTest(CsvParserTest):
+    @unittest.skipUnless(os.path.exists('/dev/full'), 'needs /dev/full')
+    def test_enospc_is_reported(self):
+        self.write('in.csv', 'a\n' + ''.join('%d\n' % number for number in range(5)))
+        with open('/dev/full', 'w') as full:
+            result = subprocess.run([sys.executable, SCRIPT, 'in.csv'], stdout=full, stderr=subprocess.PIPE, text=True, cwd=self.dir)
+        self.assertEqual(result.returncode, 3)
+        self.assertRegex(result.stderr, r'no space left on device after processing \d+ records; the output is incomplete')
+
+
+class SplitByTest(CsvParserTest):
+    def test_one_file_per_value(self):
+        self.write('in.csv', 'k,v\nx,1\ny,2\nx,3\n')
+        self.run_parser('--split-by', 'k', '-o', 'out.csv', 'in.csv')
+        self.assertEqual(self.read('out_x.csv'), 'k,v\nx,1\nx,3\n')
+        self.assertEqual(self.read('out_y.csv'), 'k,v\ny,2\n')
+
+    def test_values_with_the_same_file_name_stay_apart(self):
+        self.write('in.csv', 'k,v\na b,1\na_b,2\nA_B,3\na b,4\n')
+        result = self.run_parser('--split-by', 'k', '-o', 'out.csv', 'in.csv')
+        self.assertEqual(self.read('out_a_b.csv'), 'k,v\na b,1\na b,4\n')
+        self.assertEqual(self.read('out_a_b_2.csv'), 'k,v\na_b,2\n')
+        self.assertEqual(self.read('out_A_B_3.csv'), 'k,v\nA_B,3\n')
+        self.assertIn("'a_b' has the same file name as 'a b'", result.stderr)
+
+
+class BgzipOutputTest(CsvParserTest):
+    def test_valid_gzip_with_block_offsets(self):
+        text = 'id,name\n' + ''.join('%d,%s\n' % (number, 'x' * (number % 50)) for number in range(10000))
+        self.write('in.csv', text)
+        self.run_parser('--bgzip-output', '-o', 'out.csv.gz', 'in.csv')
+        data = self.read('out.csv.gz', 'rb')
+        plain = gzip.decompress(data).decode()
+        self.assertEqual(plain.replace('\r\n', '\n'), text)
+        index = self.read('out.csv.gz.gzi', 'rb')
+        count, = struct.unpack('<Q', index[:8])
+        self.assertGreater(count, 1)
+        self.assertEqual(len(index), 8 + 16 * count)
+        for number in range(count):
+            compressed, uncompressed = struct.unpack('<QQ', index[8 + 16 * number:24 + 16 * number])
+            # Every offset starts a BGZF block holding the data from that point on.
+            self.assertEqual(data[compressed:compressed + 4], b'\x1f\x8b\x08\x04')
+            self.assertEqual(gzip.decompress(data[compressed:]).decode(), plain[uncompressed:])
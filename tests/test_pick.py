@@ -0,0 +1,22 @@
+import sys
+import unittest
+from unittest import mock
+
+from tests.helpers import load_module
+
+
+class ResolvePickTest(unittest.TestCase):
+    def setUp(self):
+        self.csvparser = load_module()
+
+    def test_checked_columns_become_field_numbers(self):
+        self.assertEqual(self.csvparser.resolvePick([True, False, True], 1), ['1', '3'])
+        self.assertEqual(self.csvparser.resolvePick([False, True, True], 0), ['1', '2'])
+
+    def test_cancel_or_nothing_checked(self):
+        self.assertIsNone(self.csvparser.resolvePick(None, 1))
+        self.assertIsNone(self.csvparser.resolvePick([False, False], 1))
+
+    def test_loads_without_curses(self):
+        with mock.patch.dict(sys.modules, {'curses': None}):
+            load_module()
@@ -0,0 +1,48 @@
+from tests.helpers import CsvParserTest
+
+
+class RenameTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'col_a,col_b,col_c\n1,2,3\n')
+
+    def test_rename_selected_column(self):
+        result = self.run_parser('-c', 'col_c,col_a', '--rename', 'col_a=first', 'in.csv')
+        self.assertEqual(result.stdout, 'col_c,first\n3,1\n')
+
+    def test_explicit_rename_wins_over_regex(self):
+        result = self.run_parser('--rename-regex', '/^col_//', '--rename', 'col_a=X', 'in.csv')
+        self.assertEqual(result.stdout, 'X,b,c\n1,2,3\n')
+
+    def test_unknown_rename_source(self):
+        result = self.run_parser('--rename', 'nope=X', 'in.csv', status=2)
+        self.assertIn('no such column: nope', result.stderr)
+
+
+class RenameRegexTest(CsvParserTest):
+    def test_strip_shared_prefix(self):
+        self.write('in.csv', 'col_a,col_b\n1,2\n')
+        result = self.run_parser('--rename-regex', '/^col_//', 'in.csv')
+        self.assertEqual(result.stdout, 'a,b\n1,2\n')
+
+    def test_collision_created_by_renaming(self):
+        self.write('in.csv', 'col_a,a\n1,2\n')
+        result = self.run_parser('--rename-regex', '/^col_//', 'in.csv', status=2)
+        self.assertIn('duplicate header after renaming: a', result.stderr)
+
+    def test_duplicates_already_in_input_are_left_alone(self):
+        self.write('in.csv', 'a,a,b \n1,2,3\n')
+        result = self.run_parser('--trim-header-names', 'in.csv')
+        self.assertEqual(result.stdout, 'a,a,b\n1,2,3\n')
+
+
+class TrimHeaderNamesTest(CsvParserTest):
+    def test_select_by_trimmed_name(self):
+        self.write('in.csv', ' id ,\tname\n 1 , a \n')
+        result = self.run_parser('--trim-header-names', '-c', 'name,id', 'in.csv')
+        self.assertEqual(result.stdout, 'name,id\n a , 1 \n')
+
+    def test_duplicate_created_by_trimming(self):
+        self.write('in.csv', 'id,id \n1,2\n')
+        result = self.run_parser('--trim-header-names', 'in.csv', status=2)
+        self.assertIn('duplicate header after renaming: id', result.stderr)
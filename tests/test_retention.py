@@ -0,0 +1,9 @@
+from tests.helpers import CsvParserTest
+
+
+class RetentionTest(CsvParserTest):
+    def test_split_around_cutoff(self):
+        self.write('in.csv', 'id,day\n1,2023-12-31\n2,2024-01-01\n3,2024-06-01\n4,bad\n')
+        self.run_parser('--retention', 'day:2024-01-01', '--old-out', 'old.csv', '--current-out', 'new.csv', 'in.csv')
+        self.assertEqual(self.read('old.csv'), 'id,day\n1,2023-12-31\n')
+        self.assertEqual(self.read('new.csv'), 'id,day\n2,2024-01-01\n3,2024-06-01\n4,bad\n')
@@ -0,0 +1,83 @@
+from tests.helpers import CsvParserTest
+
+
+class SkipLimitTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'n\n' + ''.join('%d\n' % number for number in range(20)))
+
+    def test_limit_counts_filtered_rows(self):
+        result = self.run_parser('--filter', 'n > 9', '--limit', '3', 'in.csv')
+        self.assertEqual(result.stdout, 'n\n10\n11\n12\n')
+
+    def test_skip_and_limit_after_dedupe(self):
+        self.write('dup.csv', 'n\n1\n1\n2\n2\n3\n3\n4\n')
+        result = self.run_parser('--dedupe', '--skip', '1', '--limit', '2', 'dup.csv')
+        self.assertEqual(result.stdout, 'n\n2\n3\n')
+
+    def test_limit_after_sort_keeps_the_top_rows(self):
+        result = self.run_parser('--sort-by', 'n', '--numeric', '--sort-desc', '--limit', '2', 'in.csv')
+        self.assertEqual(result.stdout, 'n\n19\n18\n')
+
+
+class FillTest(CsvParserTest):
+    def test_short_row_is_filled_and_full_row_untouched(self):
+        self.write('short.csv', 'a,b,c\n1\n4,5,6\n')
+        result = self.run_parser('--on-field-count-mismatch-fill', 'NA', 'short.csv')
+        self.assertEqual(result.stdout, 'a,b,c\n1,NA,NA\n4,5,6\n')
+
+
+class MarkersTest(CsvParserTest):
+    def test_block_between_markers(self):
+        self.write('report.txt', 'Quarterly report\nsome prose, with a comma\nBEGIN_DATA\nid,name\n1,a\n2,b\nEND_DATA\nclosing remarks\n')
+        result = self.run_parser('--start-marker', 'BEGIN_DATA', '--end-marker', 'END_DATA', 'report.txt')
+        self.assertEqual(result.stdout, 'id,name\n1,a\n2,b\n')
+        self.assertEqual(result.stderr, '')
+
+    def test_missing_end_marker_reads_to_eof_with_a_warning(self):
+        self.write('report.txt', 'prose\nBEGIN_DATA\nid,name\n1,a\n')
+        result = self.run_parser('--start-marker', 'BEGIN_DATA', '--end-marker', 'END_DATA', 'report.txt')
+        self.assertEqual(result.stdout, 'id,name\n1,a\n')
+        self.assertIn('warning: end marker not found, read to end of input: END_DATA', result.stderr)
+
+
+class FilterTest(CsvParserTest):
+    def setUp(self):
+        super().setUp()
+        self.write('in.csv', 'name,amount,status\na,5,active\nb,100,inactive\nc,250,active\nd,1e3,pending\ne,9,n/a\n')
+
+    def names(self, expression, *args):
+        result = self.run_parser('--filter', expression, '-c', 'name', *args, 'in.csv')
+        return result.stdout.split()[1:]
+
+    def test_equal(self):
+        self.assertEqual(self.names('status==active'), ['a', 'c'])
+        self.assertEqual(self.names('amount==1000'), ['d'])
+
+    def test_not_equal(self):
+        self.assertEqual(self.names('status!=active'), ['b', 'd', 'e'])
+
+    def test_greater_is_numeric(self):
+        self.assertEqual(self.names('amount>100'), ['c', 'd'])
+
+    def test_less(self):
+        self.assertEqual(self.names('amount<100'), ['a', 'e'])
+
+    def test_greater_or_equal(self):
+        self.assertEqual(self.names('amount>=100'), ['b', 'c', 'd'])
+
+    def test_less_or_equal(self):
+        self.assertEqual(self.names('amount<=100'), ['a', 'b', 'e'])
+
+    def test_substring(self):
+        self.assertEqual(self.names('status~=act'), ['a', 'b', 'c'])
+
+    def test_string_comparison_when_not_numeric(self):
+        self.assertEqual(self.names('status>m'), ['d', 'e'])
+
+    def test_column_by_index(self):
+        self.assertEqual(self.names('3==pending'), ['d'])
+
+    def test_bad_expression(self):
+        result = self.run_parser('--filter', 'status', 'in.csv', status=2)
+        self.assertIn("expected 'COLUMN OP VALUE'", result.stderr)
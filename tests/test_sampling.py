@@ -0,0 +1,26 @@
+import collections
+
+from tests.helpers import CsvParserTest
+
+
+class SamplePerGroupTest(CsvParserTest):
+    def test_at_most_k_per_group_and_stable_for_a_seed(self):
+        self.write('in.csv', 'g,n\n' + ''.join('%s,%d\n' % ('xyz'[number % 3], number) for number in range(60)) + 'w,99\n')
+        first = self.run_parser('--sample-per-group', 'g:4', '--seed', '7', 'in.csv').stdout
+        second = self.run_parser('--sample-per-group', 'g:4', '--seed', '7', 'in.csv').stdout
+        self.assertEqual(first, second)
+        rows = [line.split(',') for line in first.splitlines()[1:]]
+        self.assertEqual(collections.Counter(group for group, number in rows), {'x': 4, 'y': 4, 'z': 4, 'w': 1})
+        self.assertNotEqual(first, self.run_parser('--sample-per-group', 'g:4', '--seed', '8', 'in.csv').stdout)
+
+
+class HeadTailTest(CsvParserTest):
+    def test_first_and_last_rows(self):
+        self.write('in.csv', 'n\n' + ''.join('%d\n' % number for number in range(1000)))
+        result = self.run_parser('--sample-head-tail', '3', 'in.csv')
+        self.assertEqual(result.stdout, 'n\n0\n1\n2\n997\n998\n999\n')
+
+    def test_short_input_is_not_duplicated(self):
+        self.write('in.csv', 'n\n1\n2\n3\n4\n5\n')
+        result = self.run_parser('--sample-head-tail', '3', 'in.csv')
+        self.assertEqual(result.stdout, 'n\n1\n2\n3\n4\n5\n')
@@ -0,0 +1,45 @@
+import json
+
+from tests.helpers import CsvParserTest
+
+
+class JsonSchemaTest(CsvParserTest):
+    def test_property_types(self):
+        self.write('in.csv', 'id,price,ok,day,at,name\n1,2.5,true,2024-01-02,2024-01-02T03:04:05Z,x\n2,,false,2024-02-03,2024-02-03,y\n')
+        self.run_parser('--json-schema-out', 'schema.json', 'in.csv')
+        schema = json.loads(self.read('schema.json'))
+        self.assertEqual(schema['$schema'], 'http://json-schema.org/draft-07/schema#')
+        self.assertEqual(schema['properties'], {
+            'id': {'type': 'integer'},
+            'price': {'type': ['number', 'null']},
+            'ok': {'type': 'boolean'},
+            'day': {'type': 'string', 'format': 'date'},
+            'at': {'type': 'string', 'format': 'date-time'},
+            'name': {'type': 'string'},
+        })
+        self.assertEqual(schema['required'], ['id', 'ok', 'day', 'at', 'name'])
+
+    def test_select_type_date_includes_timestamps(self):
+        self.write('in.csv', 'day,at,n\n2024-01-02,2024-01-02T03:04:05,1\n')
+        result = self.run_parser('--select-type', 'date', 'in.csv')
+        self.assertEqual(result.stdout, 'day,at\n2024-01-02,2024-01-02T03:04:05\n')
+
+
+class SchemaOrderTest(CsvParserTest):
+    def test_inputs_in_different_orders_share_one_schema(self):
+        self.write('order.txt', 'id\nname\nprice\n')
+        self.write('first.csv', 'price,id,name\n2.5,1,x\n')
+        self.write('second.csv', 'name,extra,id\ny,e,2\n')
+        first = self.run_parser('--schema-order-file', 'order.txt', 'first.csv')
+        self.assertEqual(first.stdout, 'id,name,price\n1,x,2.5\n')
+        second = self.run_parser('--schema-order-file', 'order.txt', 'second.csv')
+        self.assertEqual(second.stdout, 'id,name,price,extra\n2,y,,e\n')
+        dropped = self.run_parser('--schema-order-file', 'order.txt', '--drop-extra', 'second.csv')
+        self.assertEqual(dropped.stdout, 'id,name,price\n2,y,\n')
+
+
+class SelectTypeTest(CsvParserTest):
+    def test_numeric_columns_only(self):
+        self.write('in.csv', 'id,name,price,ok,day,empty\n1,x,2.5,true,2024-01-02,\n2,y,,false,2024-01-03,\n3,z,-1e3,true,2024-01-04,\n')
+        result = self.run_parser('--select-type', 'numeric', 'in.csv')
+        self.assertEqual(result.stdout, 'id,price\n1,2.5\n2,\n3,-1e3\n')
@@ -0,0 +1,80 @@
+import io
+import json
+import os
+import threading
+import types
+from unittest import mock
+
+from tests.helpers import CsvParserTest, load_module
+
+
+class StatsIntervalTest(CsvParserTest):
+    def test_snapshots_during_paced_input(self):
+        process = self.start_parser('--stats-interval', '0.2')
+        self.feed_slowly(process, ['a,b\n'] + ['%d,x\n' % number for number in range(5)], 0.2)
+        out, err = process.communicate()
+        self.assertEqual(process.returncode, 0, err)
+        snapshots = [line for line in err.splitlines() if line.startswith('stats: ')]
+        self.assertGreaterEqual(len(snapshots), 2)
+        self.assertIn('records/sec', snapshots[0])
+
+    def test_interval_must_be_positive(self):
+        for value in ('-1', '0'):
+            result = self.run_parser('--stats-interval', value, input='a\n1\n', status=2)
+            self.assertIn('expected a positive number: %r' % value, result.stderr)
+
+
+class PrescanTest(CsvParserTest):
+    def test_prescan_total_excludes_header_and_comments(self):
+        self.write('in.csv', '#a\nk\n1\n#b\n2\n"3\n4"\n')
+        result = self.run_parser('--comment-char', '#', '--prescan', '--stats', 'in.csv')
+        self.assertIn('stats: 3/3 records (100.0%)', result.stderr)
+
+    def test_progress_total_is_the_prescan_count(self):
+        csvparser = load_module()
+        counter = types.SimpleNamespace(bytes=1000)
+        stats = csvparser.Stats(4, None, counter)
+        stats.records = 2
+        done = threading.Event()
+        done.set()
+        with mock.patch('sys.stderr', new_callable=io.StringIO) as stderr:
+            csvparser.reportProgress(stats, 1000000, done)
+        self.assertIn(' 50.0%', stderr.getvalue())
+        self.assertIn('2/4 records', stderr.getvalue())
+
+
+class TimingHistogramTest(CsvParserTest):
+    def test_one_sample_per_record(self):
+        self.write('in.csv', 'a\n' + ''.join('%d\n' % number for number in range(25)))
+        result = self.run_parser('--timing-histogram', 'in.csv')
+        self.assertRegex(result.stderr, r'timing: 25 samples, p50 [\d.e+-]+us, p90 ')
+
+    def test_percentiles(self):
+        histogram = load_module().Histogram()
+        for seconds in [0.001] * 9 + [0.5]:
+            histogram.record(seconds)
+        self.assertEqual(histogram.samples, 10)
+        self.assertEqual(histogram.percentile(50), 0.001)
+        self.assertEqual(histogram.percentile(100), 0.5)
+
+
+class ColumnStatsTest(CsvParserTest):
+    def test_mean_and_stddev(self):
+        self.write('in.csv', 'n,x,name\n2,1.5,a\n4,,b\n6,2.5,c\n')
+        self.run_parser('--column-stats-out', 'stats.csv', 'in.csv')
+        self.assertEqual(self.read('stats.csv'), 'column,count,min,max,mean,stddev\nn,3,2,6,4,2\nx,2,1.5,2.5,2,0.707106781186548\n')
+
+
+class BytesReadTest(CsvParserTest):
+    def test_bytes_match_the_file_length(self):
+        content = 'id,name\n' + ''.join('%d,"quoted, %d"\n' % (number, number) for number in range(3000))
+        path = self.write('in.csv', content)
+        result = self.run_parser('--stats-format', 'json', 'in.csv')
+        stats = json.loads(result.stderr)
+        self.assertEqual(stats['records'], 3000)
+        self.assertEqual(stats['bytes'], os.path.getsize(path))
+
+    def test_text_stats_report_bytes(self):
+        path = self.write('in.csv', 'a\n1\n2\n')
+        result = self.run_parser('--stats', 'in.csv')
+        self.assertIn('%d bytes' % os.path.getsize(path), result.stderr)
@@ -0,0 +1,104 @@
+import csv
+import io
+import os
+import select
+import signal
+import subprocess
+import sys
+import time
+
+from tests.helpers import CsvParserTest, SCRIPT
+
+
+class LineBufferedTest(CsvParserTest):
+    def read_chunk(self, stream):
+        ready, _, _ = select.select([stream], [], [], 5)
+        self.assertTrue(ready, 'no output before the input ended')
+        return os.read(stream.fileno(), 65536)
+
+    def test_each_row_is_flushed_whole_before_eof(self):
+        process = subprocess.Popen([sys.executable, SCRIPT, '--line-buffered', '-f', '2,1'], stdin=subprocess.PIPE, stdout=subprocess.PIPE, stderr=subprocess.PIPE)
+        self.addCleanup(process.__exit__, None, None, None)
+        self.addCleanup(process.kill)
+        process.stdin.write(b'a,b\n1,"x\n')
+        process.stdin.flush()
+        process.stdin.write(b'y"\n')
+        process.stdin.flush()
+        output = b''
+        while output.count(b'\n') < 3:
+            chunk = self.read_chunk(process.stdout)
+            self.assertTrue(chunk.endswith(b'\n'), chunk)
+            output += chunk
+        self.assertEqual(output, b'b,a\r\n"x\ny",1\r\n')
+        for number in range(2, 5):
+            process.stdin.write(b'%d,z\n' % number)
+            process.stdin.flush()
+            self.assertEqual(self.read_chunk(process.stdout), b'z,%d\r\n' % number)
+        process.stdin.close()
+        self.assertEqual(process.wait(5), 0)
+
+
+class WaitForInputTest(CsvParserTest):
+    def test_file_that_appears_later_is_processed(self):
+        process = self.start_parser('--wait-for-input', '10', 'in.csv')
+        self.addCleanup(process.kill)
+        time.sleep(0.7)
+        self.assertIsNone(process.poll())
+        self.write('partial.csv', 'a,b\n1,2\n')
+        os.replace(self.path('partial.csv'), self.path('in.csv'))
+        stdout, stderr = process.communicate(timeout=10)
+        self.assertEqual(process.returncode, 0, stderr)
+        self.assertEqual(stdout, 'a,b\n1,2\n')
+
+    def test_missing_file_times_out(self):
+        started = time.time()
+        result = self.run_parser('--wait-for-input', '0.5', 'in.csv', status=3)
+        self.assertGreaterEqual(time.time() - started, 0.5)
+        self.assertIn("timed out after 0.5s waiting for 'in.csv'", result.stderr)
+
+    def test_timeout_must_be_positive(self):
+        for value in ('-1', '0'):
+            result = self.run_parser('--wait-for-input', value, 'in.csv', status=2)
+            self.assertIn('expected a positive number: %r' % value, result.stderr)
+
+
+class InterruptTest(CsvParserTest):
+    def test_sigint_keeps_complete_rows_and_exits_130(self):
+        process = self.start_parser('-o', 'out.csv')
+        self.addCleanup(process.kill)
+        process.stdin.write('id,note\n' + ''.join('%d,"row\n%d"\n' % (number, number) for number in range(500)))
+        process.stdin.flush()
+        time.sleep(0.5)
+        process.send_signal(signal.SIGINT)
+        # The run stops after the row in progress once more input arrives.
+        try:
+            for number in range(500, 100000):
+                process.stdin.write('%d,"row\n%d"\n' % (number, number))
+            process.stdin.close()
+        except BrokenPipeError:
+            pass
+        stdout, stderr = process.communicate(timeout=10)
+        self.assertEqual(process.returncode, 130, stderr)
+        self.assertIn('interrupted: ', stderr)
+        rows = list(csv.reader(io.StringIO(self.read('out.csv'))))
+        self.assertEqual(rows[0], ['id', 'note'])
+        self.assertGreaterEqual(len(rows), 501)
+        self.assertLess(len(rows), 100001)
+        self.assertEqual(rows[1:], [[str(number), 'row\n%d' % number] for number in range(len(rows) - 1)])
+
+    def test_second_sigint_while_input_is_idle(self):
+        process = self.start_parser('-o', 'out.csv')
+        self.addCleanup(process.kill)
+        process.stdin.write('id,note\n' + ''.join('%d,x\n' % number for number in range(100)))
+        process.stdin.flush()
+        time.sleep(0.5)
+        # The first Ctrl-C only takes effect between rows, and none arrive.
+        process.send_signal(signal.SIGINT)
+        time.sleep(0.3)
+        self.assertIsNone(process.poll())
+        process.send_signal(signal.SIGINT)
+        self.assertEqual(process.wait(5), 130)
+        stdout, stderr = process.communicate()
+        self.assertNotIn('Traceback', stderr)
+        self.assertIn('interrupted: 100 records', stderr)
+        self.assertEqual(self.read('out.csv'), 'id,note\n' + ''.join('%d,x\n' % number for number in range(100)))
@@ -0,0 +1,57 @@
+from tests.helpers import CsvParserTest
+
+
+class DryValidateTest(CsvParserTest):
+    def test_field_count_errors_are_all_reported(self):
+        self.write('in.csv', 'a,b\n1,2\n3\n4,5,6\n')
+        result = self.run_parser('--dry-validate', '--validate', 'in.csv', status=1)
+        self.assertEqual(result.stdout, '')
+        self.assertIn('2 problems', result.stderr)
+        self.assertIn('row 3 (line 3, byte 8): expected 2 fields, got 1', result.stderr)
+        self.assertIn('row 4 (line 4, byte 10): expected 2 fields, got 3', result.stderr)
+
+    def test_strict_stops_at_the_first_problem_with_status_1(self):
+        self.write('in.csv', 'a,b\n1,2\n3\n4,5,6\n')
+        result = self.run_parser('--dry-validate', '--strict', '--validate', 'in.csv', status=1)
+        self.assertIn('row 3', result.stderr)
+        self.assertNotIn('row 4', result.stderr)
+
+    def test_different_validations_are_reported_together(self):
+        self.write('in.csv', 'id,note\n1,a\n3,"b\nc"\n2,d\n')
+        result = self.run_parser('--dry-validate', '--validate-no-newlines', '--assert-monotonic', 'id', 'in.csv', status=1)
+        self.assertEqual(result.stdout, '')
+        self.assertIn('validation failed with 2 problems', result.stderr)
+        self.assertIn('record 2 (line 3) has a newline in a field', result.stderr)
+        self.assertIn("line 5: '2' is less than the previous value", result.stderr)
+
+    def test_clean_input_exits_0(self):
+        self.write('in.csv', 'id,note\n1,a\n2,b\n')
+        result = self.run_parser('--dry-validate', '--validate', '--assert-monotonic', 'id', 'in.csv')
+        self.assertEqual(result.stdout, '')
+
+
+class NoNewlinesTest(CsvParserTest):
+    def test_embedded_newline_fails_with_the_record(self):
+        self.write('in.csv', 'a,b\n1,2\n3,"x\ny"\n')
+        result = self.run_parser('--validate-no-newlines', 'in.csv', status=4)
+        self.assertIn('record 2 (line 3) has a newline in a field', result.stderr)
+
+    def test_single_line_fields_pass(self):
+        self.write('in.csv', 'a,b\n1,2\n3,"x y"\n')
+        self.assertEqual(self.run_parser('--validate-no-newlines', 'in.csv').stdout, 'a,b\n1,2\n3,x y\n')
+
+
+class MonotonicTest(CsvParserTest):
+    def test_decreasing_value_fails_with_its_line(self):
+        self.write('in.csv', 'id,v\n1,a\n3,b\n2,c\n')
+        result = self.run_parser('--assert-monotonic', 'id', 'in.csv', status=4)
+        self.assertIn("line 4: '2' is less than the previous value", result.stderr)
+
+    def test_sorted_dates_pass(self):
+        self.write('in.csv', 'at\n2024-01-01\n2024-01-01\n2024-03-05\n')
+        self.assertEqual(self.run_parser('--assert-monotonic', 'at', 'in.csv').stdout, 'at\n2024-01-01\n2024-01-01\n2024-03-05\n')
+
+    def test_strict_rejects_repeats(self):
+        self.write('in.csv', 'at\n2024-01-01\n2024-01-01\n')
+        result = self.run_parser('--assert-monotonic', 'at', '--monotonic-strict', 'in.csv', status=4)
+        self.assertIn("line 3: '2024-01-01' repeats the previous value", result.stderr)